@@ -1,13 +1,212 @@
 use anyhow::{Context, Result};
 use dialoguer::{Input, Password};
-use reqwest::blocking::{Client, Response, multipart};
+use indicatif::ProgressBar;
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder, Response, multipart};
 use reqwest::header;
+use reqwest::{Certificate, Method, StatusCode};
 use serde::Deserialize;
+use serde::de::DeserializeOwned;
 use serde_json::json;
-use std::fs::File;
-use std::path::Path;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tracing::warn;
 
 use crate::config::Config;
+use crate::errors::ByteStashyError;
+use crate::language;
+use crate::models::{Capabilities, NewSnippet, Snippet, SnippetRef};
+use crate::upload::UploadFile;
+
+/// Default upper bound for API response bodies (64 MiB)
+pub const DEFAULT_MAX_RESPONSE_BYTES: u64 = 64 * 1024 * 1024;
+/// Default upper bound for the files uploaded by one command (10 MiB)
+pub const DEFAULT_MAX_UPLOAD_BYTES: u64 = 10 * 1024 * 1024;
+/// Default timeout for a single HTTP request in seconds
+pub const DEFAULT_TIMEOUT_SECS: u64 = 30;
+/// Default number of retries for read requests hitting a transient failure
+pub const DEFAULT_MAX_RETRIES: u32 = 3;
+/// Delay before the first retry; doubled for each further attempt
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+/// Longest `Retry-After` a read request waits for before giving up
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+/// Timeout of `ping`, short so an unreachable server is reported quickly
+pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+/// Bytes of an error response read to explain the failure
+const MAX_ERROR_BODY_BYTES: u64 = 64 * 1024;
+/// Characters of an error response kept in the error message
+const MAX_ERROR_MESSAGE_CHARS: usize = 4 * 1024;
+
+/// Outcome of [`APIClient::ping`]
+#[derive(Debug)]
+pub enum PingStatus {
+    /// The key was accepted; carries the response time
+    Ok(Duration),
+    /// The server rejected the API key
+    Unauthorized,
+    /// The server answered with another status
+    Unexpected(StatusCode),
+    /// No response, e.g. DNS, connection, TLS or timeout failures
+    Unreachable(reqwest::Error),
+}
+
+/// Per-invocation overrides for client settings, usually taken from global CLI flags
+#[derive(Default, Clone)]
+pub struct ClientOptions {
+    pub max_response_size: Option<u64>,
+    /// Extra headers from `--header`, applied on top of the configured ones
+    pub headers: Vec<(String, String)>,
+    /// Permit extra headers to replace the authentication headers
+    pub allow_auth_header_override: bool,
+    /// Fail keyring access after this long instead of waiting for an unlock prompt
+    pub keyring_timeout: Option<Duration>,
+    /// Server profile from `--profile`; the configured default otherwise
+    pub profile: Option<String>,
+    /// Request timeout in seconds from `--timeout`
+    pub timeout_secs: Option<u64>,
+    /// Proxy for all requests from `--proxy`; `HTTP_PROXY`/`HTTPS_PROXY` apply otherwise
+    pub proxy: Option<String>,
+    /// Skip TLS certificate verification from `--insecure`
+    pub insecure: bool,
+    /// Extra trusted CA certificate from `--cacert`
+    pub cacert: Option<PathBuf>,
+}
+
+impl ClientOptions {
+    /// Effective request timeout: flag, then config, then the default
+    pub fn timeout_secs(&self, cfg: &Config) -> u64 {
+        self.timeout_secs
+            .or(cfg.timeout_secs)
+            .unwrap_or(DEFAULT_TIMEOUT_SECS)
+    }
+
+    /// Whether TLS certificates go unchecked, from the flag or the config
+    pub fn insecure(&self, cfg: &Config) -> bool {
+        self.insecure || cfg.insecure == Some(true)
+    }
+
+    /// Extra CA certificate to trust: flag, then config
+    pub fn cacert<'a>(&'a self, cfg: &'a Config) -> Option<&'a Path> {
+        self.cacert.as_deref().or(cfg.cacert.as_deref())
+    }
+}
+
+/// Headers used for authentication, which extra headers must not replace by accident
+const AUTH_HEADERS: [&str; 2] = ["x-api-key", "bytestashauth"];
+
+/// Merge configured and command-line extra headers into a header map
+fn extra_headers(cfg: &Config, options: &ClientOptions) -> Result<header::HeaderMap> {
+    let mut headers = header::HeaderMap::new();
+    let configured = cfg
+        .extra_headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()));
+    let flags = options
+        .headers
+        .iter()
+        .map(|(name, value)| (name.as_str(), value.as_str()));
+
+    for (name, value) in configured.chain(flags) {
+        let name = header::HeaderName::from_bytes(name.as_bytes())
+            .with_context(|| format!("Invalid header name: {name}"))?;
+        if AUTH_HEADERS.contains(&name.as_str()) && !options.allow_auth_header_override {
+            anyhow::bail!(
+                "Refusing to override authentication header '{}'. Pass --allow-auth-header-override to force it.",
+                name
+            );
+        }
+        let value = header::HeaderValue::from_str(value)
+            .with_context(|| format!("Invalid value for header {name}"))?;
+        headers.insert(name, value);
+    }
+
+    Ok(headers)
+}
+
+/// Client builder with the connection settings (timeout, proxy) shared by every
+/// HTTP request bytestashy makes, including requests to other services
+pub fn http_client_builder(cfg: &Config, options: &ClientOptions) -> Result<ClientBuilder> {
+    let mut builder = Client::builder().timeout(Duration::from_secs(options.timeout_secs(cfg)));
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy).context("Invalid proxy URL")?);
+    }
+    Ok(builder)
+}
+
+/// Read a PEM CA certificate for `--cacert`
+pub fn load_ca_certificate(path: &Path) -> Result<Certificate> {
+    let pem =
+        fs::read(path).with_context(|| format!("Cannot read CA certificate {}", path.display()))?;
+    Certificate::from_pem(&pem)
+        .with_context(|| format!("{} is not a valid PEM certificate", path.display()))
+}
+
+/// Build the HTTP client for ByteStash requests: the shared settings plus extra
+/// headers, the `--cacert` certificate and, with `--insecure`, no certificate checks
+fn build_http_client(cfg: &Config, options: &ClientOptions) -> Result<Client> {
+    let mut builder =
+        http_client_builder(cfg, options)?.default_headers(extra_headers(cfg, options)?);
+    if let Some(path) = options.cacert(cfg) {
+        builder = builder.add_root_certificate(load_ca_certificate(path)?);
+    }
+    if options.insecure(cfg) {
+        warn!(
+            "TLS certificate verification is DISABLED (--insecure): the server's identity is not checked and the API key can be intercepted"
+        );
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    Ok(builder.build()?)
+}
+
+/// Reader that fails once more than `remaining` bytes have been read
+struct LimitedReader<R> {
+    inner: R,
+    remaining: u64,
+    limit: u64,
+}
+
+impl<R: Read> Read for LimitedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            // Probe for trailing data to distinguish EOF from an oversized body
+            let mut probe = [0u8; 1];
+            return match self.inner.read(&mut probe)? {
+                0 => Ok(0),
+                _ => Err(io::Error::other(format!(
+                    "response exceeds maximum size of {} bytes",
+                    self.limit
+                ))),
+            };
+        }
+        let max = buf.len().min(self.remaining as usize);
+        let n = self.inner.read(&mut buf[..max])?;
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Reader that reports the bytes of an uploaded file to a progress bar
+struct ProgressReader<R> {
+    inner: R,
+    name: String,
+    progress: ProgressBar,
+    started: bool,
+}
+
+impl<R: Read> Read for ProgressReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if !self.started {
+            self.started = true;
+            self.progress
+                .set_message(format!("Uploading {}…", self.name));
+        }
+        let n = self.inner.read(buf)?;
+        self.progress.inc(n as u64);
+        Ok(n)
+    }
+}
 
 /// Response from login endpoint
 #[derive(Deserialize)]
@@ -20,6 +219,162 @@ struct LoginResponse {
 #[derive(Deserialize)]
 struct ApiKeyResponse {
     key: String,
+    /// Server-side key id, needed to revoke the key later
+    #[serde(default)]
+    id: Option<serde_json::Value>,
+}
+
+/// Render a JSON id (number or string) without quotes
+fn id_to_string(id: &serde_json::Value) -> String {
+    match id {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Response of create, update and delete calls; only the id is relied upon
+#[derive(Deserialize)]
+struct IdResponse {
+    id: Option<serde_json::Value>,
+}
+
+/// Turn a create/update/delete response into a reference to the snippet
+fn snippet_ref(api_url: &str, response: IdResponse) -> Result<SnippetRef> {
+    let id = response
+        .id
+        .as_ref()
+        .map(id_to_string)
+        .context("Server response missing snippet ID")?;
+    Ok(SnippetRef {
+        url: snippet_url(api_url, &id),
+        id,
+    })
+}
+
+/// Web UI address of a snippet
+pub fn snippet_url(api_url: &str, id: &str) -> String {
+    format!("{api_url}/snippets/{id}")
+}
+
+/// The server answered with HTTP 429
+#[derive(Debug)]
+pub struct RateLimited {
+    /// Wait requested by the `Retry-After` header
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.retry_after {
+            Some(wait) => write!(f, "rate limited, retry after {} seconds", wait.as_secs()),
+            None => write!(f, "rate limited, retry later"),
+        }
+    }
+}
+
+impl std::error::Error for RateLimited {}
+
+/// Parse a `Retry-After` value, given either in seconds or as an HTTP date
+fn parse_retry_after(value: &str, now: chrono::DateTime<chrono::Utc>) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    // A date in the past means "now"
+    Some((at.to_utc() - now).to_std().unwrap_or_default())
+}
+
+/// Readable text of an error response body: the `error`/`message` field of a
+/// JSON body, the text of an HTML page, or the body itself, shortened to
+/// `MAX_ERROR_MESSAGE_CHARS`. Falls back to the status reason when empty.
+fn error_message(status: StatusCode, content_type: Option<&str>, body: &[u8]) -> String {
+    let body = String::from_utf8_lossy(body);
+    let body = body.trim();
+    let json_message = serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| {
+            ["error", "message"]
+                .into_iter()
+                .find_map(|field| value.get(field)?.as_str().map(str::to_string))
+        });
+    let is_html = content_type.is_some_and(|value| value.contains("html")) || body.starts_with('<');
+    let message = match json_message {
+        Some(message) => message,
+        None if is_html => strip_html(body),
+        None => body.to_string(),
+    };
+
+    let message = message.trim();
+    if message.is_empty() {
+        return status
+            .canonical_reason()
+            .unwrap_or("no details from the server")
+            .to_string();
+    }
+    match message.char_indices().nth(MAX_ERROR_MESSAGE_CHARS) {
+        Some((end, _)) => format!("{}… (truncated)", &message[..end]),
+        None => message.to_string(),
+    }
+}
+
+/// Text of an HTML page without tags, scripts and styles, whitespace collapsed
+fn strip_html(html: &str) -> String {
+    let mut text = String::new();
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        text.push_str(&rest[..start]);
+        text.push(' ');
+        let tag = &rest[start..];
+        let skip_to = ["script", "style"]
+            .into_iter()
+            .find(|name| {
+                tag.get(1..=name.len())
+                    .is_some_and(|t| t.eq_ignore_ascii_case(name))
+            })
+            .and_then(|name| tag.to_ascii_lowercase().find(&format!("</{name}")));
+        let tag = &tag[skip_to.unwrap_or(0)..];
+        rest = match tag.find('>') {
+            Some(end) => &tag[end + 1..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    let text = text
+        .replace("&nbsp;", " ")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&");
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// [`ByteStashyError::Api`] for an unexpected response, explained by its body
+fn api_error(resp: Response) -> anyhow::Error {
+    let status = resp.status();
+    let content_type = resp
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let mut body = Vec::new();
+    let _ = resp.take(MAX_ERROR_BODY_BYTES).read_to_end(&mut body);
+    ByteStashyError::api(
+        status.as_u16(),
+        error_message(status, content_type.as_deref(), &body),
+    )
+    .into()
+}
+
+fn rate_limited(resp: &Response) -> RateLimited {
+    RateLimited {
+        retry_after: resp
+            .headers()
+            .get(header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| parse_retry_after(value, chrono::Utc::now())),
+    }
 }
 
 /// HTTP client for ByteStash API operations
@@ -27,32 +382,152 @@ pub struct APIClient {
     client: Client,
     pub(crate) api_url: String,
     api_key: String,
+    max_response_size: u64,
+    request_timeout: Option<Duration>,
+    max_retries: u32,
+    /// Reports the bytes of uploaded files
+    upload_progress: ProgressBar,
+}
+
+/// Backoff before retry number `attempt` (0-based): 0.5s, 1s, 2s, ...
+fn retry_delay(attempt: u32) -> Duration {
+    RETRY_BASE_DELAY.saturating_mul(2u32.saturating_pow(attempt))
 }
 
 impl APIClient {
     /// Create new API client from saved config
-    pub fn new() -> Result<APIClient> {
-        if let Some(cfg) = Config::load()? {
-            let client = Client::builder().build()?;
-            Ok(APIClient {
-                client,
-                api_url: cfg.api_url,
-                api_key: cfg.api_key,
-            })
-        } else {
-            anyhow::bail!("No saved api key found. Run `bytestashy login <api-url>`.");
+    pub fn new(options: &ClientOptions) -> Result<APIClient> {
+        match Config::load(options.profile.as_deref(), options.keyring_timeout)? {
+            Some(cfg) => Self::with_config(&cfg, &cfg.api_url, &cfg.api_key, options),
+            None => anyhow::bail!("No saved api key found. Run `bytestashy login <api-url>`."),
         }
     }
 
+    /// API client for `api_url` and `api_key` using the client settings from `cfg`
+    fn with_config(
+        cfg: &Config,
+        api_url: &str,
+        api_key: &str,
+        options: &ClientOptions,
+    ) -> Result<APIClient> {
+        Ok(APIClient {
+            client: build_http_client(cfg, options)?,
+            api_url: api_url.trim_end_matches('/').to_string(),
+            api_key: api_key.to_string(),
+            max_response_size: options
+                .max_response_size
+                .or(cfg.max_response_bytes)
+                .unwrap_or(DEFAULT_MAX_RESPONSE_BYTES),
+            request_timeout: None,
+            max_retries: cfg.max_retries.unwrap_or(DEFAULT_MAX_RETRIES),
+            upload_progress: ProgressBar::hidden(),
+        })
+    }
+
+    /// Check that `api_key` is accepted by the server with a read-only request,
+    /// without touching the saved config
+    pub fn check_key(api_url: &str, api_key: &str, options: &ClientOptions) -> Result<()> {
+        let cfg = Config::load_file()?.unwrap_or_default();
+        Self::with_config(&cfg, api_url, api_key, options)?
+            .list()
+            .map(|_| ())
+    }
+
     /// Interactive login flow - authenticate and create API key
-    pub fn login_and_create_key(api_url: &str) -> Result<()> {
+    pub fn login_and_create_key(api_url: &str, options: &ClientOptions) -> Result<()> {
+        let base = api_url.trim_end_matches('/');
+        let mut cfg = Config::load_file()?.unwrap_or_default();
+        // One client for both requests, so the TLS connection is reused
+        let http_client = build_http_client(&cfg, options)?;
+        let jwt_token = Self::authenticate(&http_client, base)?;
+        let key_data = Self::create_api_key(&http_client, base, &jwt_token)?;
+
+        cfg.prepare_login(options.profile.as_deref());
+        cfg.api_url = base.to_string();
+        cfg.api_key = key_data.key;
+        cfg.api_key_id = key_data.id.map(|id| id_to_string(&id));
+        cfg.save(options.keyring_timeout)
+            .context("Error saving config")?;
+        println!("Login successful, api key saved to keyring");
+
+        Ok(())
+    }
+
+    /// Create a new API key, verify it and replace the stored one.
+    /// The stored key is only overwritten once the new key has been verified.
+    pub fn rotate_key(revoke_old: bool, options: &ClientOptions) -> Result<()> {
+        let Some(mut cfg) = Config::load(options.profile.as_deref(), options.keyring_timeout)?
+        else {
+            anyhow::bail!("No saved api key found. Run `bytestashy login <api-url>`.");
+        };
+        let base = cfg.api_url.trim_end_matches('/').to_string();
+        let http_client = build_http_client(&cfg, options)?;
+
+        let jwt_token = Self::authenticate(&http_client, &base)?;
+        let key_data = Self::create_api_key(&http_client, &base, &jwt_token)?;
+
+        // Make sure the new key works before replacing the stored one
+        let resp = http_client
+            .get(format!("{base}/api/v1/snippets"))
+            .header("x-api-key", &key_data.key)
+            .send()
+            .context("Error verifying new api key (GET /api/v1/snippets)")?;
+        if !resp.status().is_success() {
+            anyhow::bail!(
+                "New api key failed verification (HTTP {}). The stored key was not changed.",
+                resp.status()
+            );
+        }
+
+        let old_key_id = cfg.api_key_id.take();
+        cfg.api_key = key_data.key;
+        cfg.api_key_id = key_data.id.map(|id| id_to_string(&id));
+        cfg.save(options.keyring_timeout)
+            .context("Error saving config")?;
+        println!("New api key verified and saved to keyring");
+
+        if revoke_old {
+            match old_key_id {
+                Some(id) => {
+                    // The new key is already saved, so a failed revoke only warns
+                    match http_client
+                        .delete(format!("{base}/api/keys/{id}"))
+                        .header("bytestashauth", format!("bearer {jwt_token}"))
+                        .send()
+                    {
+                        Ok(resp) if resp.status().is_success() => {
+                            println!("Old api key revoked on the server");
+                        }
+                        Ok(resp) => {
+                            eprintln!(
+                                "Could not revoke old api key (HTTP {}). Remove it in the ByteStash web UI.",
+                                resp.status()
+                            );
+                        }
+                        Err(err) => {
+                            eprintln!(
+                                "Could not revoke old api key ({err}). Remove it in the ByteStash web UI."
+                            );
+                        }
+                    }
+                }
+                None => {
+                    eprintln!(
+                        "The id of the old api key is unknown. Remove it in the ByteStash web UI."
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Prompt for credentials and exchange them for a JWT
+    fn authenticate(http_client: &Client, base: &str) -> Result<String> {
         let username: String = Input::new().with_prompt("Username").interact_text()?;
         let password: String = Password::new().with_prompt("Password").interact()?;
 
-        let base = api_url.trim_end_matches('/');
         let login_endpoint = format!("{base}/api/auth/login");
-        let http_client = Client::new();
-
         let resp = http_client
             .post(&login_endpoint)
             .json(&json!({ "username": username, "password": password }))
@@ -64,17 +539,18 @@ impl APIClient {
             if resp.status().as_u16() == 401 {
                 anyhow::bail!("Invalid credentials (401 Unauthorized).");
             } else {
-                let status = resp.status();
-                let text = resp.text().unwrap_or_default();
-                anyhow::bail!("Login error: HTTP {} – {}", status, text);
+                anyhow::bail!("Login error: {}", api_error(resp));
             }
         }
 
         let login_data: LoginResponse = resp
             .json()
             .context("Invalid response, unable to parse JSON")?;
-        let jwt_token = login_data.token;
+        Ok(login_data.token)
+    }
 
+    /// Prompt for a key name and create a new API key using a JWT
+    fn create_api_key(http_client: &Client, base: &str, jwt_token: &str) -> Result<ApiKeyResponse> {
         let key_name: String = Input::new()
             .with_prompt("Name of the api key to generate")
             .default("bytestashy".into())
@@ -91,24 +567,12 @@ impl APIClient {
 
         // Check API key creation was successful
         if resp_key.status().as_u16() != 201 {
-            let status = resp_key.status();
-            let text = resp_key.text().unwrap_or_default();
-            anyhow::bail!("api key generation failed: HTTP {} – {}", status, text);
+            anyhow::bail!("api key generation failed: {}", api_error(resp_key));
         }
 
-        let key_data: ApiKeyResponse = resp_key
+        resp_key
             .json()
-            .context("Invalid response from /api/keys, couldn't parse JSON")?;
-        let api_key = key_data.key;
-
-        let cfg = Config {
-            api_url: base.to_string(),
-            api_key: api_key.clone(),
-        };
-        cfg.save().context("Error saving config")?;
-        println!("Login successful, api key saved to keyring");
-
-        Ok(())
+            .context("Invalid response from /api/keys, couldn't parse JSON")
     }
 
     /// Build HTTP headers with API key authentication
@@ -121,45 +585,96 @@ impl APIClient {
         headers
     }
 
+    /// Limit how long each individual request may take, e.g. per item of a batch
+    pub fn set_request_timeout(&mut self, timeout: Option<Duration>) {
+        self.request_timeout = timeout;
+    }
+
+    /// Report the bytes of files uploaded from disk to `progress`
+    pub fn set_upload_progress(&mut self, progress: ProgressBar) {
+        self.upload_progress = progress;
+    }
+
+    /// Start an authenticated request against the API
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let builder = self
+            .client
+            .request(method, url)
+            .headers(self.api_key_header());
+        match self.request_timeout {
+            Some(timeout) => builder.timeout(timeout),
+            None => builder,
+        }
+    }
+
+    /// Send an idempotent request, retrying connection errors and 5xx responses
+    /// with exponential backoff. HTTP 429 is retried after the server's
+    /// `Retry-After` unless that exceeds a minute. Never use this for requests
+    /// that create or change data.
+    fn send_with_retry(&self, builder: RequestBuilder, max_retries: u32) -> Result<Response> {
+        let mut attempt = 0;
+        loop {
+            let Some(request) = builder.try_clone() else {
+                return Ok(builder.send()?);
+            };
+            let (transient, delay) = match request.send() {
+                Ok(resp) if resp.status().is_server_error() && attempt < max_retries => {
+                    (format!("HTTP {}", resp.status()), retry_delay(attempt))
+                }
+                Ok(resp)
+                    if resp.status() == StatusCode::TOO_MANY_REQUESTS && attempt < max_retries =>
+                {
+                    let limit = rate_limited(&resp);
+                    match limit.retry_after {
+                        Some(wait) if wait > MAX_RATE_LIMIT_WAIT => return Ok(resp),
+                        wait => (limit.to_string(), wait.unwrap_or(retry_delay(attempt))),
+                    }
+                }
+                Err(err) if err.is_connect() && attempt < max_retries => {
+                    (err.to_string(), retry_delay(attempt))
+                }
+                result => return Ok(result?),
+            };
+            warn!(
+                "Request failed ({}), retrying in {:.1}s ({}/{})",
+                transient,
+                delay.as_secs_f32(),
+                attempt + 1,
+                max_retries
+            );
+            std::thread::sleep(delay);
+            attempt += 1;
+        }
+    }
+
     /// Fetch all user snippets
-    pub fn list(&self) -> Result<serde_json::Value> {
+    pub fn list(&self) -> Result<Vec<Snippet>> {
         let url = format!("{}/api/v1/snippets", self.api_url);
         let resp = self
-            .client
-            .get(&url)
-            .headers(self.api_key_header())
-            .send()
+            .send_with_retry(self.request(Method::GET, &url), self.max_retries)
             .context("Error sending GET request to /api/v1/snippets")?;
 
         match resp.status().as_u16() {
-            200 => {
-                let json: serde_json::Value = resp
-                    .json()
-                    .context("Error parsing JSON response from /api/v1/snippets")?;
-                Ok(json)
-            }
+            200 => self
+                .read_json(resp)
+                .context("Error parsing JSON response from /api/v1/snippets"),
             401 => {
                 anyhow::bail!(
                     "Error 401: api key is invalid. Run 'bytestashy login <url>' to regenerate it."
                 );
             }
-            other => {
-                let text = resp.text().unwrap_or_default();
-                anyhow::bail!("Error {}: {}", other, text);
-            }
+            429 => Err(rate_limited(&resp).into()),
+            _ => Err(api_error(resp)),
         }
     }
 
     /// Fetch single snippet by ID
-    pub fn get_snippet(&self, id: &usize) -> Result<serde_json::Value> {
+    pub fn get_snippet(&self, id: &usize) -> Result<Snippet> {
         let url = format!("{}/api/v1/snippets/{}", self.api_url, id);
         let resp = self
-            .client
-            .get(&url)
-            .headers(self.api_key_header())
-            .send()
+            .send_with_retry(self.request(Method::GET, &url), self.max_retries)
             .context("Error sending GET request to /api/v1/snippets")?;
-        self.check_result(resp)
+        self.check_snippet_result(id, resp)
     }
 
     /// Upload files and create new snippet
@@ -169,52 +684,156 @@ impl APIClient {
         description: &str,
         is_public: bool,
         categories: &str,
-        file_paths: &[String],
-    ) -> Result<serde_json::Value> {
+        files: &[UploadFile],
+    ) -> Result<SnippetRef> {
         let url = format!("{}/api/v1/snippets/push", self.api_url);
-        let mut form = multipart::Form::new()
-            .text("title", title.to_string())
-            .text("description", description.to_string())
-            .text("is_public", is_public.to_string())
-            .text("categories", categories.to_string());
-
-        // Add each file to multipart form
-        for path_str in file_paths {
-            let path = Path::new(path_str);
-            let file_name = path
-                .file_name()
-                .and_then(|osstr| osstr.to_str())
-                .unwrap_or("unknown");
-            let file =
-                File::open(path).with_context(|| format!("Couldn't read file: {path_str}"))?;
-            form = form.part(
-                "files",
-                multipart::Part::reader(file).file_name(file_name.to_string()),
-            );
-        }
+        let form = Self::attach_files(
+            Self::snippet_form(title, description, is_public, categories),
+            files,
+            0,
+            &self.upload_progress,
+        )?;
 
         // Send request
         let resp = self
-            .client
-            .post(&url)
-            .headers(self.api_key_header())
+            .request(Method::POST, &url)
             .multipart(form)
             .send()
             .context("Error sending POST request to /api/v1/snippets/push")?;
 
-        self.check_result(resp)
+        snippet_ref(&self.api_url, self.check_result(resp)?)
+    }
+
+    /// Create a snippet from in-memory fragments instead of files on disk
+    pub fn create_snippet_from_fragments(&self, snippet: &NewSnippet) -> Result<SnippetRef> {
+        let url = format!("{}/api/v1/snippets/push", self.api_url);
+        let resp = self
+            .request(Method::POST, &url)
+            .multipart(Self::fragments_form(snippet))
+            .send()
+            .context("Error sending POST request to /api/v1/snippets/push")?;
+
+        snippet_ref(&self.api_url, self.check_result(resp)?)
+    }
+
+    /// Replace a snippet with in-memory fragments instead of files on disk
+    pub fn update_snippet_from_fragments(
+        &self,
+        id: &usize,
+        snippet: &NewSnippet,
+    ) -> Result<SnippetRef> {
+        let url = format!("{}/api/v1/snippets/{}", self.api_url, id);
+        let resp = self
+            .request(Method::PUT, &url)
+            .multipart(Self::fragments_form(snippet))
+            .send()
+            .context("Error sending PUT request to /api/v1/snippets")?;
+
+        snippet_ref(&self.api_url, self.check_snippet_result(id, resp)?)
+    }
+
+    /// Replace a snippet with in-memory fragments followed by files from disk
+    pub fn update_snippet_with_files(
+        &self,
+        id: &usize,
+        snippet: &NewSnippet,
+        files: &[UploadFile],
+    ) -> Result<SnippetRef> {
+        let url = format!("{}/api/v1/snippets/{}", self.api_url, id);
+        let form = Self::attach_files(
+            Self::fragments_form(snippet),
+            files,
+            snippet.fragments.len(),
+            &self.upload_progress,
+        )?;
+        let resp = self
+            .request(Method::PUT, &url)
+            .multipart(form)
+            .send()
+            .context("Error sending PUT request to /api/v1/snippets")?;
+
+        snippet_ref(&self.api_url, self.check_snippet_result(id, resp)?)
+    }
+
+    /// Build a multipart form carrying metadata and in-memory fragments.
+    /// Every file part is followed by its `languages` and `positions` fields;
+    /// positions count from 0 in the order the files are attached.
+    fn fragments_form(snippet: &NewSnippet) -> multipart::Form {
+        let mut form = Self::snippet_form(
+            &snippet.title,
+            &snippet.description,
+            snippet.is_public,
+            &snippet.categories.join(","),
+        );
+        for (position, fragment) in snippet.fragments.iter().enumerate() {
+            let language = fragment
+                .language
+                .clone()
+                .unwrap_or_else(|| language::detect_language(&fragment.file_name).to_string());
+            form = form
+                .part(
+                    "files",
+                    multipart::Part::text(fragment.code.clone())
+                        .file_name(fragment.file_name.clone()),
+                )
+                .text("languages", language)
+                .text("positions", position.to_string());
+        }
+        form
+    }
+
+    /// Build the metadata part of a snippet multipart form
+    fn snippet_form(
+        title: &str,
+        description: &str,
+        is_public: bool,
+        categories: &str,
+    ) -> multipart::Form {
+        multipart::Form::new()
+            .text("title", title.to_string())
+            .text("description", description.to_string())
+            .text("is_public", is_public.to_string())
+            .text("categories", categories.to_string())
+    }
+
+    /// Add each file to multipart form, reporting the bytes sent to `progress`.
+    /// Positions continue from `first_position`, after fragments already in the form.
+    fn attach_files(
+        mut form: multipart::Form,
+        files: &[UploadFile],
+        first_position: usize,
+        progress: &ProgressBar,
+    ) -> Result<multipart::Form> {
+        for (position, upload) in (first_position..).zip(files) {
+            let file = File::open(&upload.path)
+                .with_context(|| format!("Couldn't read file: {}", upload.path))?;
+            let length = file.metadata()?.len();
+            let reader = ProgressReader {
+                inner: file,
+                name: upload.name.clone(),
+                progress: progress.clone(),
+                started: false,
+            };
+            form = form
+                .part(
+                    "files",
+                    multipart::Part::reader_with_length(reader, length)
+                        .file_name(upload.name.clone()),
+                )
+                .text("languages", upload.language().to_string())
+                .text("positions", position.to_string());
+        }
+        Ok(form)
     }
 
     /// Delete snippet by ID
-    pub fn delete_snippet(&self, id: &usize) -> Result<serde_json::Value> {
+    pub fn delete_snippet(&self, id: &usize) -> Result<SnippetRef> {
         let url = format!("{}/api/v1/snippets/{}", self.api_url, id);
         let resp = self
-            .client
-            .delete(&url)
-            .headers(self.api_key_header())
+            .request(Method::DELETE, &url)
             .send()
             .context("Error sending DELETE request to /api/v1/snippets")?;
-        self.check_result(resp)
+        snippet_ref(&self.api_url, self.check_snippet_result(id, resp)?)
     }
 
     /// Update existing snippet with new files and metadata
@@ -225,41 +844,24 @@ impl APIClient {
         description: &str,
         is_public: bool,
         categories: &str,
-        file_paths: &[String],
-    ) -> Result<serde_json::Value> {
+        files: &[UploadFile],
+    ) -> Result<SnippetRef> {
         let url = format!("{}/api/v1/snippets/{}", self.api_url, id);
-        let mut form = multipart::Form::new()
-            .text("title", title.to_string())
-            .text("description", description.to_string())
-            .text("is_public", is_public.to_string())
-            .text("categories", categories.to_string());
-
-        // Attach files
-        // Add each file to multipart form
-        for path_str in file_paths {
-            let path = Path::new(path_str);
-            let file_name = path
-                .file_name()
-                .and_then(|osstr| osstr.to_str())
-                .unwrap_or("unknown");
-            let file =
-                File::open(path).with_context(|| format!("Couldn't read file: {path_str}"))?;
-            form = form.part(
-                "files",
-                multipart::Part::reader(file).file_name(file_name.to_string()),
-            );
-        }
+        let form = Self::attach_files(
+            Self::snippet_form(title, description, is_public, categories),
+            files,
+            0,
+            &self.upload_progress,
+        )?;
 
         // Send request
         let resp = self
-            .client
-            .put(&url)
-            .headers(self.api_key_header())
+            .request(Method::PUT, &url)
             .multipart(form)
             .send()
             .context("Error sending PUT request to /api/v1/snippets")?;
 
-        self.check_result(resp)
+        snippet_ref(&self.api_url, self.check_snippet_result(id, resp)?)
     }
 
     /// Search snippets with query parameters
@@ -268,7 +870,7 @@ impl APIClient {
         query: &str,
         sort: Option<&str>,
         search_code: Option<bool>,
-    ) -> Result<serde_json::Value> {
+    ) -> Result<Vec<Snippet>> {
         let mut url = format!("{}/api/v1/snippets/search", self.api_url);
         // Build query parameters
         let mut params = Vec::new();
@@ -288,41 +890,466 @@ impl APIClient {
         }
 
         let resp = self
-            .client
-            .get(&url)
-            .headers(self.api_key_header())
-            .send()
+            .send_with_retry(self.request(Method::GET, &url), self.max_retries)
             .context("Error sending GET request to /api/v1/snippets/search")?;
         self.check_result(resp)
     }
 
+    /// Detect optional server features using read-only requests only
+    pub fn probe(&self) -> Result<Capabilities> {
+        // Paginating servers wrap the list in an object; pinning shows up as a snippet field
+        let url = format!("{}/api/v1/snippets?page=1&limit=1", self.api_url);
+        let resp = self
+            .request(Method::GET, &url)
+            .send()
+            .context("Error sending GET request to /api/v1/snippets")?;
+        let listing: serde_json::Value = self.check_result(resp)?;
+        let first = match &listing {
+            serde_json::Value::Array(items) => items.first(),
+            other => other
+                .get("data")
+                .and_then(|data| data.as_array())
+                .and_then(|items| items.first()),
+        };
+
+        Ok(Capabilities {
+            search_code: self
+                .endpoint_exists("/api/v1/snippets/search?q=bytestashy-probe&searchCode=true")?,
+            pagination: Some(listing.get("pagination").is_some()),
+            share_links: self.endpoint_exists("/api/v1/share")?,
+            trash: self.endpoint_exists("/api/v1/snippets/recycled")?,
+            pinning: first.map(|snippet| snippet.get("is_pinned").is_some()),
+            categories: self.endpoint_exists("/api/v1/categories")?,
+            probed_at: chrono::Utc::now().to_rfc3339(),
+        })
+    }
+
+    /// One small authenticated request with a short timeout and no retries,
+    /// telling network, key and server problems apart
+    pub fn ping(&self) -> PingStatus {
+        let url = format!("{}/api/v1/snippets?page=1&limit=1", self.api_url);
+        let started = Instant::now();
+        match self.request(Method::GET, &url).timeout(PING_TIMEOUT).send() {
+            Ok(resp) => match resp.status() {
+                status if status.is_success() => PingStatus::Ok(started.elapsed()),
+                StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => PingStatus::Unauthorized,
+                status => PingStatus::Unexpected(status),
+            },
+            Err(err) => PingStatus::Unreachable(err),
+        }
+    }
+
+    /// Whether a GET on `path` is served with JSON; `None` when inconclusive
+    fn endpoint_exists(&self, path: &str) -> Result<Option<bool>> {
+        let url = format!("{}{}", self.api_url, path);
+        let resp = self
+            .request(Method::GET, &url)
+            .send()
+            .with_context(|| format!("Error sending GET request to {path}"))?;
+        // Unknown routes may fall through to the web UI, so require a JSON body
+        let is_json = resp
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .is_some_and(|value| value.contains("json"));
+        Ok(match resp.status().as_u16() {
+            200..=299 => Some(is_json),
+            404 | 405 | 501 => Some(false),
+            _ => None,
+        })
+    }
+
+    /// Deserialize a response body while enforcing the maximum response size.
+    /// The body is parsed straight from the socket instead of being buffered first.
+    fn read_json<T: DeserializeOwned>(&self, resp: Response) -> Result<T> {
+        if let Some(length) = resp.content_length()
+            && length > self.max_response_size
+        {
+            anyhow::bail!(
+                "Response of {} bytes exceeds the maximum of {} bytes. Use --max-response-size to raise the limit.",
+                length,
+                self.max_response_size
+            );
+        }
+
+        let reader = LimitedReader {
+            inner: resp,
+            remaining: self.max_response_size,
+            limit: self.max_response_size,
+        };
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Like `check_result`, but HTTP 404 means snippet `id` does not exist and is
+    /// reported as [`ByteStashyError::NotFound`]
+    fn check_snippet_result<T: DeserializeOwned>(&self, id: &usize, resp: Response) -> Result<T> {
+        if resp.status() == StatusCode::NOT_FOUND {
+            return Err(ByteStashyError::NotFound { id: *id }.into());
+        }
+        self.check_result(resp)
+    }
+
     /// Parse HTTP response and handle common error codes
-    fn check_result(&self, resp: Response) -> Result<serde_json::value::Value> {
+    fn check_result<T: DeserializeOwned>(&self, resp: Response) -> Result<T> {
         match resp.status().as_u16() {
-            200 => {
-                let json: serde_json::Value = resp
-                    .json()
-                    .context("Error parsing JSON response from /api/v1/snippets")?;
-                Ok(json)
-            }
-            201 => {
-                let json: serde_json::Value = resp
-                    .json()
-                    .context("Error parsing JSON response from /api/v1/snippets/push")?;
-                Ok(json)
-            }
+            200 => self
+                .read_json(resp)
+                .context("Error parsing JSON response from /api/v1/snippets"),
+            201 => self
+                .read_json(resp)
+                .context("Error parsing JSON response from /api/v1/snippets/push"),
             401 => {
                 anyhow::bail!(
                     "Error 401: api key is invalid. Run 'bytestashy login <url>' to regenerate it."
                 );
             }
-            404 => {
-                anyhow::bail!("Error 404: Snippet not found");
+            429 => Err(rate_limited(&resp).into()),
+            _ => Err(api_error(resp)),
+        }
+    }
+}
+
+#[cfg(test)]
+impl APIClient {
+    /// Client for a local test server, without retries
+    pub fn for_test(api_url: &str) -> APIClient {
+        APIClient {
+            client: Client::new(),
+            api_url: api_url.to_string(),
+            api_key: "key".to_string(),
+            max_response_size: DEFAULT_MAX_RESPONSE_BYTES,
+            request_timeout: None,
+            max_retries: 0,
+            upload_progress: ProgressBar::hidden(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snippet_ref_accepts_numeric_and_string_ids() {
+        let response: IdResponse = serde_json::from_str(r#"{"id": 42, "title": "x"}"#).unwrap();
+        let snippet = snippet_ref("https://stash.example.com", response).unwrap();
+        assert_eq!(snippet.id, "42");
+        assert_eq!(snippet.url, "https://stash.example.com/snippets/42");
+
+        let response: IdResponse = serde_json::from_str(r#"{"id": "7"}"#).unwrap();
+        assert_eq!(snippet_ref("", response).unwrap().id, "7");
+
+        let response: IdResponse = serde_json::from_str("{}").unwrap();
+        assert!(snippet_ref("", response).is_err());
+    }
+
+    #[test]
+    fn test_extra_headers_merge_flags_over_config() {
+        let mut cfg = Config::default();
+        cfg.extra_headers
+            .insert("CF-Access-Client-Id".to_string(), "from-config".to_string());
+        let options = ClientOptions {
+            headers: vec![("cf-access-client-id".to_string(), "from-flag".to_string())],
+            ..Default::default()
+        };
+
+        let headers = extra_headers(&cfg, &options).unwrap();
+
+        assert_eq!(headers.len(), 1);
+        assert_eq!(headers["cf-access-client-id"], "from-flag");
+    }
+
+    #[test]
+    fn test_extra_headers_refuse_auth_override() {
+        let options = ClientOptions {
+            headers: vec![("X-Api-Key".to_string(), "other".to_string())],
+            ..Default::default()
+        };
+        assert!(extra_headers(&Config::default(), &options).is_err());
+
+        let options = ClientOptions {
+            allow_auth_header_override: true,
+            ..options
+        };
+        assert!(extra_headers(&Config::default(), &options).is_ok());
+    }
+
+    #[test]
+    fn test_insecure_from_flag_or_config() {
+        let mut cfg = Config::default();
+        let options = ClientOptions::default();
+        assert!(!options.insecure(&cfg));
+
+        cfg.insecure = Some(true);
+        assert!(options.insecure(&cfg));
+        assert!(build_http_client(&cfg, &options).is_ok());
+
+        cfg.insecure = Some(false);
+        let options = ClientOptions {
+            insecure: true,
+            ..options
+        };
+        assert!(options.insecure(&cfg));
+    }
+
+    #[test]
+    fn test_error_message_from_body() {
+        let bad_request = StatusCode::BAD_REQUEST;
+        assert_eq!(
+            error_message(bad_request, None, br#"{"error":"Title is required"}"#),
+            "Title is required"
+        );
+        assert_eq!(
+            error_message(bad_request, None, b"plain failure\n"),
+            "plain failure"
+        );
+        assert_eq!(
+            error_message(StatusCode::BAD_GATEWAY, Some("text/html"), b""),
+            "Bad Gateway"
+        );
+
+        let page = "<html><head><style>body { color: red }</style><title>502</title></head>\
+                    <body><h1>Bad&nbsp;Gateway</h1><script>alert(1)</script><p>nginx &amp; co</p></body></html>";
+        assert_eq!(
+            error_message(
+                StatusCode::BAD_GATEWAY,
+                Some("text/html; charset=utf-8"),
+                page.as_bytes()
+            ),
+            "502 Bad Gateway nginx & co"
+        );
+
+        let long = "é".repeat(MAX_ERROR_MESSAGE_CHARS + 10);
+        let message = error_message(bad_request, None, long.as_bytes());
+        assert!(message.ends_with("… (truncated)"));
+        assert_eq!(
+            message.chars().filter(|&c| c == 'é').count(),
+            MAX_ERROR_MESSAGE_CHARS
+        );
+    }
+
+    #[test]
+    fn test_limited_reader_within_limit() {
+        let mut reader = LimitedReader {
+            inner: &b"{\"id\":1}"[..],
+            remaining: 8,
+            limit: 8,
+        };
+        let mut out = String::new();
+        reader.read_to_string(&mut out).unwrap();
+
+        assert_eq!(out, "{\"id\":1}");
+    }
+
+    #[test]
+    fn test_limited_reader_rejects_oversized_body() {
+        let reader = LimitedReader {
+            inner: &b"{\"id\":12345}"[..],
+            remaining: 4,
+            limit: 4,
+        };
+        let result: serde_json::Result<serde_json::Value> = serde_json::from_reader(reader);
+
+        assert!(result.unwrap_err().to_string().contains("maximum size"));
+    }
+
+    #[test]
+    fn test_progress_reader_counts_bytes() {
+        let progress = ProgressBar::hidden();
+        let mut reader = ProgressReader {
+            inner: &b"fn main() {}\n"[..],
+            name: "main.rs".to_string(),
+            progress: progress.clone(),
+            started: false,
+        };
+
+        let mut body = String::new();
+        reader.read_to_string(&mut body).unwrap();
+
+        assert_eq!(progress.position(), body.len() as u64);
+        assert_eq!(progress.message(), "Uploading main.rs…");
+    }
+
+    #[test]
+    fn test_retry_delay_doubles() {
+        assert_eq!(retry_delay(0), Duration::from_millis(500));
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(3), Duration::from_secs(4));
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let now = chrono::DateTime::parse_from_rfc3339("2015-10-21T07:28:00Z")
+            .unwrap()
+            .to_utc();
+
+        assert_eq!(
+            parse_retry_after("120", now),
+            Some(Duration::from_secs(120))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:28:30 GMT", now),
+            Some(Duration::from_secs(30))
+        );
+        assert_eq!(
+            parse_retry_after("Wed, 21 Oct 2015 07:00:00 GMT", now),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn test_list_retries_server_errors() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for response in [
+                "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]",
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        let client = APIClient {
+            client: Client::new(),
+            api_url,
+            api_key: "key".to_string(),
+            max_response_size: DEFAULT_MAX_RESPONSE_BYTES,
+            request_timeout: None,
+            max_retries: 1,
+            upload_progress: ProgressBar::hidden(),
+        };
+
+        assert!(client.list().unwrap().is_empty());
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_rate_limit_retries_reads_and_reports_wait() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for response in [
+                "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 0\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]",
+                "HTTP/1.1 429 Too Many Requests\r\nRetry-After: 300\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+        let client = APIClient {
+            client: Client::new(),
+            api_url,
+            api_key: "key".to_string(),
+            max_response_size: DEFAULT_MAX_RESPONSE_BYTES,
+            request_timeout: None,
+            max_retries: 1,
+            upload_progress: ProgressBar::hidden(),
+        };
+
+        assert!(client.list().unwrap().is_empty());
+        // Waits longer than a minute are reported instead of slept through
+        let err = client.get_snippet(&1).unwrap_err();
+        let limit = err.downcast_ref::<RateLimited>().unwrap();
+        assert_eq!(limit.retry_after, Some(Duration::from_secs(300)));
+        assert_eq!(err.to_string(), "rate limited, retry after 300 seconds");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn test_ping_classifies_responses() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for response in [
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: 2\r\nConnection: close\r\n\r\n[]",
+                "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                "HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+            ] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                stream.write_all(response.as_bytes()).unwrap();
             }
-            other => {
-                let text = resp.text().unwrap_or_default();
-                anyhow::bail!("Error {}: {}", other, text);
+        });
+        let mut client = APIClient {
+            client: Client::new(),
+            api_url,
+            api_key: "key".to_string(),
+            max_response_size: DEFAULT_MAX_RESPONSE_BYTES,
+            request_timeout: None,
+            max_retries: 1,
+            upload_progress: ProgressBar::hidden(),
+        };
+
+        assert!(matches!(client.ping(), PingStatus::Ok(_)));
+        assert!(matches!(client.ping(), PingStatus::Unauthorized));
+        assert!(matches!(
+            client.ping(),
+            PingStatus::Unexpected(StatusCode::INTERNAL_SERVER_ERROR)
+        ));
+        server.join().unwrap();
+
+        // Nothing listens on the discard port
+        client.api_url = "http://127.0.0.1:9".to_string();
+        assert!(matches!(client.ping(), PingStatus::Unreachable(_)));
+    }
+
+    #[test]
+    fn test_missing_snippet_is_not_found() {
+        use std::io::Write;
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let api_url = format!("http://{}", listener.local_addr().unwrap());
+        let server = std::thread::spawn(move || {
+            for _ in 0..3 {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut request = [0u8; 4096];
+                let _ = stream.read(&mut request);
+                stream
+                    .write_all(
+                        b"HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    )
+                    .unwrap();
             }
-        }
+        });
+        let client = APIClient {
+            client: Client::new(),
+            api_url,
+            api_key: "key".to_string(),
+            max_response_size: DEFAULT_MAX_RESPONSE_BYTES,
+            request_timeout: None,
+            max_retries: 0,
+            upload_progress: ProgressBar::hidden(),
+        };
+        let not_found = |err: anyhow::Error| match err.downcast::<ByteStashyError>() {
+            Ok(ByteStashyError::NotFound { id }) => Some(id),
+            _ => None,
+        };
+
+        assert_eq!(not_found(client.get_snippet(&4).unwrap_err()), Some(4));
+        assert_eq!(not_found(client.delete_snippet(&5).unwrap_err()), Some(5));
+        // A missing search endpoint is not a missing snippet
+        assert_eq!(
+            not_found(client.search_snippets("q", None, None).unwrap_err()),
+            None
+        );
+        server.join().unwrap();
     }
 }
@@ -0,0 +1,182 @@
+use crate::cli::BackupFormat;
+use crate::models::{NewSnippet, Snippet};
+use crate::sync;
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// Archive shape for `path`: `.tar` files are tar archives, everything else JSON
+pub fn format_for(path: &str) -> BackupFormat {
+    match Path::new(path).extension() {
+        Some(ext) if ext.eq_ignore_ascii_case("tar") => BackupFormat::Tar,
+        _ => BackupFormat::Json,
+    }
+}
+
+/// Write `snippets` as a JSON array, or as a tar archive with one
+/// `<id>/snippet.json` per snippet plus its files under `<id>/files/` for
+/// browsing. `snippet.json` holds everything needed to restore the snippet.
+pub fn write_archive(snippets: &[Snippet], format: BackupFormat, out: impl Write) -> Result<()> {
+    match format {
+        BackupFormat::Json => serde_json::to_writer_pretty(out, snippets)?,
+        BackupFormat::Tar => write_tar(snippets, out)?,
+    }
+    Ok(())
+}
+
+fn write_tar(snippets: &[Snippet], out: impl Write) -> Result<()> {
+    let mut archive = tar::Builder::new(out);
+    for snippet in snippets {
+        let mtime = snippet
+            .updated_at_time()
+            .map(|time| time.timestamp().max(0) as u64)
+            .unwrap_or(0);
+        let mut append = |path: &Path, data: &[u8]| -> Result<()> {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(mtime);
+            archive
+                .append_data(&mut header, path, data)
+                .with_context(|| format!("Couldn't add {} to the archive", path.display()))
+        };
+
+        let dir = PathBuf::from(snippet.id.to_string());
+        append(
+            &dir.join("snippet.json"),
+            &serde_json::to_vec_pretty(snippet)?,
+        )?;
+        for fragment in &snippet.fragments {
+            // Names that would escape the snippet directory are only kept in snippet.json
+            if let Some(path) = sync::local_path(&dir.join("files"), &fragment.file_name) {
+                append(&path, fragment.code.as_bytes())?;
+            }
+        }
+    }
+    archive.into_inner()?.flush()?;
+    Ok(())
+}
+
+/// Whether `data` starts with a tar header (POSIX `ustar` magic)
+fn is_tar(data: &[u8]) -> bool {
+    data.get(257..262) == Some(b"ustar".as_slice())
+}
+
+/// Snippets from an archive written by `write_archive`, in archive order and
+/// ready to upload. The format is detected from the contents.
+pub fn read_archive(data: &[u8]) -> Result<Vec<NewSnippet>> {
+    let snippets: Vec<Snippet> = if is_tar(data) {
+        read_tar(data)?
+    } else {
+        serde_json::from_slice(data).context("Backup is neither a tar archive nor valid JSON")?
+    };
+    Ok(snippets.into_iter().map(NewSnippet::from).collect())
+}
+
+fn read_tar(data: &[u8]) -> Result<Vec<Snippet>> {
+    let mut snippets = Vec::new();
+    for entry in tar::Archive::new(data).entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        // Only `<id>/snippet.json` is authoritative; `files/` is a convenience copy
+        if path.components().count() != 2 || !path.ends_with("snippet.json") {
+            continue;
+        }
+        let mut json = Vec::new();
+        entry.read_to_end(&mut json)?;
+        snippets.push(
+            serde_json::from_slice(&json)
+                .with_context(|| format!("Invalid {} in backup", path.display()))?,
+        );
+    }
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Fragment;
+
+    fn snippet() -> Snippet {
+        Snippet {
+            id: 7,
+            title: "Deploy".to_string(),
+            description: "Helper scripts".to_string(),
+            categories: vec!["ops".to_string()],
+            fragments: vec![
+                Fragment {
+                    id: 1,
+                    file_name: "bin/deploy.sh".to_string(),
+                    code: "echo hi\n".to_string(),
+                    language: "bash".to_string(),
+                    position: 0,
+                },
+                Fragment {
+                    id: 2,
+                    file_name: "../escape.txt".to_string(),
+                    code: "nope".to_string(),
+                    language: String::new(),
+                    position: 1,
+                },
+            ],
+            updated_at: "2024-05-01T12:00:00Z".to_string(),
+            share_count: 0,
+            is_public: true,
+        }
+    }
+
+    #[test]
+    fn test_format_for_extension() {
+        assert_eq!(format_for("backup.tar"), BackupFormat::Tar);
+        assert_eq!(format_for("backup.TAR"), BackupFormat::Tar);
+        assert_eq!(format_for("backup.json"), BackupFormat::Json);
+        assert_eq!(format_for("backup"), BackupFormat::Json);
+    }
+
+    #[test]
+    fn test_write_archive_json_round_trips() {
+        let mut out = Vec::new();
+        write_archive(&[snippet()], BackupFormat::Json, &mut out).unwrap();
+
+        let restored: Vec<Snippet> = serde_json::from_slice(&out).unwrap();
+        assert_eq!(restored, vec![snippet()]);
+    }
+
+    #[test]
+    fn test_write_archive_tar_layout() {
+        let mut out = Vec::new();
+        write_archive(&[snippet()], BackupFormat::Tar, &mut out).unwrap();
+
+        let mut archive = tar::Archive::new(out.as_slice());
+        let mut entries = Vec::new();
+        for entry in archive.entries().unwrap() {
+            let mut entry = entry.unwrap();
+            let mut data = String::new();
+            entry.read_to_string(&mut data).unwrap();
+            entries.push((entry.path().unwrap().display().to_string(), data));
+        }
+
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec!["7/snippet.json", "7/files/bin/deploy.sh"]);
+        assert_eq!(
+            serde_json::from_str::<Snippet>(&entries[0].1).unwrap(),
+            snippet()
+        );
+        assert_eq!(entries[1].1, "echo hi\n");
+    }
+
+    #[test]
+    fn test_read_archive_both_formats() {
+        for format in [BackupFormat::Json, BackupFormat::Tar] {
+            let mut out = Vec::new();
+            write_archive(&[snippet()], format, &mut out).unwrap();
+
+            let restored = read_archive(&out).unwrap();
+
+            assert_eq!(restored, vec![NewSnippet::from(snippet())]);
+            assert_eq!(restored[0].fragments[1].file_name, "../escape.txt");
+            assert_eq!(restored[0].fragments[1].language, None);
+        }
+        assert!(read_archive(b"not a backup").is_err());
+    }
+}
@@ -0,0 +1,90 @@
+use colored::*;
+use std::time::Instant;
+use tracing::info;
+
+/// Outcome of running an operation over several items
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    pub total: usize,
+    pub failed: usize,
+    /// Labels of the items whose request timed out
+    pub timed_out: Vec<String>,
+}
+
+impl BatchReport {
+    pub fn succeeded(&self) -> usize {
+        self.total - self.failed
+    }
+}
+
+/// Check whether an error was caused by a request timeout
+pub fn is_timeout(err: &anyhow::Error) -> bool {
+    err.chain().any(|cause| {
+        cause
+            .downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_timeout())
+    })
+}
+
+/// Run `op` for every item, reporting per-item results and continuing past failures.
+/// `op` returns the message printed on success.
+pub fn run_batch<T>(
+    items: &[T],
+    label: impl Fn(&T) -> String,
+    mut op: impl FnMut(&T) -> anyhow::Result<String>,
+) -> BatchReport {
+    let mut report = BatchReport {
+        total: items.len(),
+        ..Default::default()
+    };
+
+    for (index, item) in items.iter().enumerate() {
+        let position = format!("[{}/{}]", index + 1, report.total);
+        let name = label(item);
+        let started = Instant::now();
+
+        match op(item) {
+            Ok(message) => {
+                println!("{position} {} {message}", name.bold());
+            }
+            Err(err) => {
+                report.failed += 1;
+                if is_timeout(&err) {
+                    eprintln!("{position} {} {}", name.bold(), "timed out".red());
+                    report.timed_out.push(name.clone());
+                } else {
+                    eprintln!("{position} {} {}: {err}", name.bold(), "failed".red());
+                }
+            }
+        }
+        info!("{} finished in {:?}", name, started.elapsed());
+    }
+
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_batch_continues_after_failures() {
+        let items = vec![1, 2, 3, 4];
+
+        let report = run_batch(
+            &items,
+            |n| format!("item {n}"),
+            |n| {
+                if n % 2 == 0 {
+                    anyhow::bail!("even");
+                }
+                Ok("ok".to_string())
+            },
+        );
+
+        assert_eq!(report.total, 4);
+        assert_eq!(report.failed, 2);
+        assert_eq!(report.succeeded(), 2);
+        assert!(report.timed_out.is_empty());
+    }
+}
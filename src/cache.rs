@@ -0,0 +1,111 @@
+use crate::models::Snippet;
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Snippet list as stored on disk
+#[derive(Serialize, Deserialize)]
+struct CacheFile<S> {
+    api_url: String,
+    /// Unix timestamp of the request
+    fetched_at: i64,
+    snippets: S,
+}
+
+/// Last `list` result of one profile, kept in the cache directory
+pub struct ListCache {
+    path: PathBuf,
+}
+
+impl ListCache {
+    /// Cache of `profile`, or `None` when there is no cache directory
+    pub fn for_profile(profile: &str) -> Option<ListCache> {
+        let dir = ProjectDirs::from("", "", "bytestashy")?
+            .cache_dir()
+            .join("list-cache");
+        Some(ListCache {
+            path: dir.join(format!("{profile}.json")),
+        })
+    }
+
+    /// Cached snippets of `api_url` fetched less than `ttl` before `now`.
+    /// A missing, stale or unreadable cache is a miss.
+    pub fn load(&self, api_url: &str, ttl: Duration, now: DateTime<Utc>) -> Option<Vec<Snippet>> {
+        let cached: CacheFile<Vec<Snippet>> =
+            serde_json::from_str(&fs::read_to_string(&self.path).ok()?).ok()?;
+        let age = u64::try_from(now.timestamp() - cached.fetched_at).ok()?;
+        (cached.api_url == api_url && age < ttl.as_secs()).then_some(cached.snippets)
+    }
+
+    pub fn store(
+        &self,
+        api_url: &str,
+        snippets: &[Snippet],
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let cached = CacheFile {
+            api_url: api_url.to_string(),
+            fetched_at: now.timestamp(),
+            snippets,
+        };
+        fs::write(&self.path, serde_json::to_string(&cached)?)?;
+        Ok(())
+    }
+
+    /// Drop the cached list, e.g. after snippets were changed
+    pub fn invalidate(&self) -> anyhow::Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snippet(id: u64) -> Snippet {
+        Snippet {
+            id,
+            title: format!("snippet {id}"),
+            description: String::new(),
+            categories: vec![],
+            fragments: vec![],
+            updated_at: String::new(),
+            share_count: 0,
+            is_public: false,
+        }
+    }
+
+    #[test]
+    fn test_list_cache_expires_and_invalidates() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = ListCache {
+            path: dir.path().join("default.json"),
+        };
+        let ttl = Duration::from_secs(60);
+        let fetched = Utc::now();
+        let later = |secs| fetched + chrono::Duration::seconds(secs);
+        assert_eq!(cache.load("https://a", ttl, fetched), None);
+
+        cache.store("https://a", &[snippet(1)], fetched).unwrap();
+
+        assert_eq!(
+            cache.load("https://a", ttl, later(30)),
+            Some(vec![snippet(1)])
+        );
+        assert_eq!(cache.load("https://a", ttl, later(90)), None);
+        assert_eq!(cache.load("https://b", ttl, later(30)), None);
+
+        cache.invalidate().unwrap();
+        assert_eq!(cache.load("https://a", ttl, later(30)), None);
+        cache.invalidate().unwrap();
+    }
+}
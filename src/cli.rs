@@ -1,4 +1,8 @@
-use clap::{Parser, Subcommand, ValueEnum};
+use crate::display::DescWidth;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use serde::{Deserialize, Serialize};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
 
 /// CLI tool for managing code snippets via ByteStash API
 #[derive(Parser)]
@@ -12,6 +16,210 @@ pub struct Cli {
     #[arg(long, help = "Generate shell completions for the specified shell")]
     pub shell: Option<Shell>,
 
+    /// Whether to color output; `auto` colors terminals unless `NO_COLOR` is set
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "WHEN",
+        default_value = "auto",
+        help = "Color output: auto, always or never"
+    )]
+    pub color: ColorChoice,
+
+    /// Show more log output: `-v` info, `-vv` debug, `-vvv` trace
+    #[arg(
+        short,
+        long,
+        global = true,
+        action = clap::ArgAction::Count,
+        help = "Show more log output (repeat for more detail)"
+    )]
+    pub verbose: u8,
+
+    /// Only log errors; command output is unaffected
+    #[arg(
+        short,
+        long,
+        global = true,
+        conflicts_with = "verbose",
+        help = "Only log errors"
+    )]
+    pub quiet: bool,
+
+    /// Abort when an API response is larger than this (e.g. 512K, 64M)
+    #[arg(
+        long,
+        global = true,
+        value_name = "SIZE",
+        value_parser = parse_size,
+        help = "Maximum accepted API response size (e.g. 512K, 64M)"
+    )]
+    pub max_response_size: Option<u64>,
+
+    /// Separator used when entering categories (defaults to ',')
+    #[arg(
+        long,
+        global = true,
+        value_name = "CHAR",
+        help = "Single character separating categories in prompts"
+    )]
+    pub category_separator: Option<char>,
+
+    /// Extra HTTP headers for every request, e.g. for authenticating gateways
+    #[arg(
+        long = "header",
+        global = true,
+        value_name = "NAME: VALUE",
+        value_parser = parse_header,
+        help = "Extra HTTP header sent with every request (repeatable)"
+    )]
+    pub headers: Vec<(String, String)>,
+
+    /// Persist titles, descriptions and categories entered at prompts for up-arrow recall
+    #[arg(
+        long,
+        global = true,
+        help = "Remember prompt input across runs (up arrow recalls it)"
+    )]
+    pub prompt_history: bool,
+
+    /// Fail instead of waiting on a keyring unlock dialog; implied when stdin is not a terminal
+    #[arg(
+        long,
+        global = true,
+        help = "Fail fast with an error instead of waiting for a locked keyring to be unlocked"
+    )]
+    pub no_keyring_prompt: bool,
+
+    /// Treat categories that differ only in case (`Rust` vs `rust`) as distinct
+    #[arg(
+        long,
+        global = true,
+        help = "Match categories case-sensitively when deduplicating"
+    )]
+    pub case_sensitive: bool,
+
+    /// Description length in `list`/`search` output; `auto` fills the terminal width
+    #[arg(
+        long,
+        global = true,
+        value_name = "N|auto",
+        help = "Truncate list descriptions to N characters, or fit the terminal with 'auto'"
+    )]
+    pub desc_width: Option<DescWidth>,
+
+    /// How destructive commands (`delete`, `update`, `sync`) ask for confirmation
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "POLICY",
+        help = "Confirm destructive operations: always (even with --force), once (per-command default) or never"
+    )]
+    pub confirm_destructive: Option<ConfirmPolicy>,
+
+    /// Give up on a request after this many seconds
+    #[arg(
+        long,
+        global = true,
+        value_name = "SECS",
+        help = "HTTP request timeout in seconds [default: 30]"
+    )]
+    pub timeout: Option<u64>,
+
+    /// Route all requests through this proxy instead of the one from the environment
+    #[arg(
+        long,
+        global = true,
+        value_name = "URL",
+        help = "Send requests through this HTTP(S) proxy (default: HTTP_PROXY/HTTPS_PROXY)"
+    )]
+    pub proxy: Option<String>,
+
+    /// Accept any TLS certificate from the ByteStash server, e.g. self-signed ones
+    #[arg(
+        short = 'k',
+        long,
+        global = true,
+        help = "Skip TLS certificate verification for the ByteStash server (insecure)"
+    )]
+    pub insecure: bool,
+
+    /// Trust the CA certificate in this PEM file in addition to the system ones
+    #[arg(
+        long,
+        global = true,
+        value_name = "PATH",
+        help = "Trust this PEM CA certificate for the ByteStash server"
+    )]
+    pub cacert: Option<PathBuf>,
+
+    /// Named server profile to use instead of the configured default
+    #[arg(
+        long,
+        global = true,
+        value_name = "NAME",
+        help = "Use the named server profile (login creates it)"
+    )]
+    pub profile: Option<String>,
+
+    /// Never pipe long `list`/`search` output or `get` code previews through a pager
+    #[arg(
+        long,
+        global = true,
+        help = "Do not page long list and search output or code previews"
+    )]
+    pub no_pager: bool,
+
+    /// Print `list`, `get` and `search` results as JSON, without prompts or decoration
+    #[arg(
+        long,
+        global = true,
+        help = "Print list, get and search results as JSON"
+    )]
+    pub json: bool,
+
+    /// Output shape for `list`, `get` and `search`; `--json` is short for `--output-format json`
+    #[arg(
+        long,
+        global = true,
+        value_enum,
+        value_name = "FORMAT",
+        conflicts_with = "json",
+        help = "Output format for list, get and search [default: table]"
+    )]
+    pub output_format: Option<OutputFormat>,
+
+    /// Pretty-print JSON output (default when writing to a terminal)
+    #[arg(long, global = true, help = "Pretty-print JSON output")]
+    pub pretty: bool,
+
+    /// Print JSON output on a single line (default when writing to a pipe)
+    #[arg(
+        long,
+        global = true,
+        conflicts_with = "pretty",
+        help = "Print JSON output on a single line"
+    )]
+    pub compact: bool,
+
+    /// Print what `create`, `copy`, `append`, `update`, `delete` and `import` would send instead of sending it
+    #[arg(
+        long,
+        global = true,
+        help = "Show the request create, copy, append, update, delete and import would send without sending it"
+    )]
+    pub dry_run: bool,
+
+    /// Allow `--header` or `extra_headers` to replace the authentication headers
+    #[arg(
+        long,
+        global = true,
+        help = "Allow extra headers to replace authentication headers"
+    )]
+    pub allow_auth_header_override: bool,
+
     #[command(subcommand)]
     pub command: Option<Commands>,
 }
@@ -26,30 +234,357 @@ pub enum Commands {
     Login {
         #[arg(help = "URL of your ByteStash server")]
         api_url: String,
-        #[arg(help = "API key to use for authentication (optional)")]
+        #[arg(
+            env = "BYTESTASHY_API_KEY",
+            hide_env_values = true,
+            help = "Existing API key to save instead of logging in with a password"
+        )]
         api_key: Option<String>,
     },
-    #[command(about = "Create a new snippet")]
+    #[command(
+        about = "Create a new snippet",
+        group = ArgGroup::new("text_source").args(["stdin", "edit"])
+    )]
     Create {
-        #[arg(help = "Files to upload")]
+        #[arg(help = "Files, directories or glob patterns to upload")]
         files: Vec<String>,
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            help = "Skip files and directories matching PATTERN (repeatable)"
+        )]
+        exclude: Vec<String>,
+        #[arg(
+            long,
+            conflicts_with = "stdin_json",
+            help = "Snippet title; skips the title prompt"
+        )]
+        title: Option<String>,
+        #[arg(
+            long,
+            conflicts_with = "stdin_json",
+            help = "Snippet description; skips the description prompt"
+        )]
+        description: Option<String>,
+        #[arg(
+            long,
+            value_name = "LIST",
+            conflicts_with = "stdin_json",
+            help = "Categories separated by the category separator; skips the categories prompt"
+        )]
+        categories: Option<String>,
+        #[arg(
+            long,
+            visible_alias = "public",
+            help = "Create the snippet as public without prompting"
+        )]
+        only_public: bool,
+        #[arg(
+            long,
+            visible_alias = "private",
+            conflicts_with = "only_public",
+            help = "Create the snippet as private without prompting"
+        )]
+        only_private: bool,
+        #[arg(
+            long,
+            conflicts_with = "files",
+            help = "Create snippets from a JSON array read from stdin"
+        )]
+        stdin_json: bool,
+        #[arg(long, help = "Allow uploading files that share the same file name")]
+        allow_duplicate_names: bool,
+        #[arg(
+            long,
+            help = "Upload even when the files exceed the maximum upload size"
+        )]
+        allow_large: bool,
+        #[arg(
+            long,
+            value_name = "SECS",
+            requires = "stdin_json",
+            help = "Abort a single bulk item after SECS seconds and continue with the next"
+        )]
+        timeout_per_file: Option<u64>,
+        #[arg(
+            long,
+            requires = "stdin_json",
+            help = "Skip snippets already created by an interrupted run of the same payload"
+        )]
+        resume: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "skip",
+            help = "What to do with files that look binary"
+        )]
+        on_binary: OnBinary,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["files", "stdin_json"],
+            help = "Create a snippet from FILE, filling {{name}} placeholders from --var"
+        )]
+        template_file: Option<String>,
+        #[arg(
+            long = "var",
+            value_name = "NAME=VALUE",
+            value_parser = parse_var,
+            requires = "template_file",
+            help = "Value for a template placeholder (repeatable)"
+        )]
+        vars: Vec<(String, String)>,
+        #[arg(
+            long,
+            requires = "template_file",
+            help = "Upload even if some template placeholders have no value"
+        )]
+        allow_unfilled: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["files", "stdin_json", "template_file"],
+            help = "Create a single-file snippet from text read from stdin"
+        )]
+        stdin: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["files", "stdin_json", "template_file", "verify", "scan_secrets", "stdin"],
+            help = "Compose a single-file snippet in $EDITOR"
+        )]
+        edit: bool,
+        #[arg(
+            long,
+            value_name = "FILENAME",
+            requires = "text_source",
+            help = "File name for the --stdin or --edit fragment [default: snippet.txt]"
+        )]
+        name: Option<String>,
+        #[arg(
+            long,
+            value_name = "LANG",
+            conflicts_with = "stdin_json",
+            help = "Language for all files instead of detecting it from the extension"
+        )]
+        language: Option<String>,
+        #[arg(long, help = "Add categories derived from the file extensions")]
+        auto_categorize: bool,
+        #[arg(
+            long,
+            help = "Type categories instead of picking from the ones already in use (skips fetching them)"
+        )]
+        no_fetch: bool,
+        #[arg(
+            long,
+            conflicts_with = "stdin_json",
+            help = "Fetch the snippet after creating it and check the stored files match"
+        )]
+        verify: bool,
+        #[arg(
+            long,
+            conflicts_with = "stdin_json",
+            help = "Check files for likely secrets before uploading"
+        )]
+        scan_secrets: bool,
     },
     #[command(about = "Retrieve a snippet by ID and write its files")]
     Get {
+        #[arg(help = "Numeric snippet identifier (omit to pick interactively)")]
+        id: Option<usize>,
+        #[arg(
+            long,
+            alias = "numbered-lines",
+            help = "Prefix previewed code with line numbers"
+        )]
+        line_numbers: bool,
+        #[arg(
+            long,
+            alias = "fragment-filter",
+            value_name = "PATTERN",
+            help = "Only preview and download fragments whose file name matches the glob"
+        )]
+        fragment_glob: Option<String>,
+        #[arg(
+            long,
+            value_name = "NAME",
+            help = "Only preview and download the fragment with this exact file name"
+        )]
+        file: Option<String>,
+        #[arg(
+            long,
+            value_name = "PATH",
+            help = "Write the single fragment to PATH (file, FIFO or - for stdout) without prompting"
+        )]
+        to: Option<String>,
+        #[arg(
+            long,
+            value_name = "COMMAND",
+            conflicts_with = "to",
+            help = "Stream the fragments into the stdin of COMMAND instead of downloading them"
+        )]
+        pipe_to: Option<String>,
+        #[arg(
+            short,
+            long,
+            conflicts_with_all = ["to", "pipe_to"],
+            help = "Copy the fragments to the clipboard instead of downloading them"
+        )]
+        clipboard: bool,
+        #[arg(
+            long,
+            value_name = "FILE",
+            conflicts_with_all = ["to", "pipe_to", "clipboard", "output"],
+            help = "Write the whole snippet as one Markdown document to FILE (- for stdout)"
+        )]
+        markdown: Option<String>,
+        #[arg(
+            long,
+            conflicts_with_all = ["to", "pipe_to", "clipboard", "markdown", "output"],
+            help = "Print the code to stdout without prompting, with a header per file for multi-file snippets"
+        )]
+        stdout: bool,
+        #[arg(long, help = "Show code previews without syntax highlighting")]
+        no_highlight: bool,
+        #[arg(
+            short,
+            long,
+            value_name = "DIR",
+            conflicts_with = "to",
+            help = "Download the fragments into DIR instead of the current directory"
+        )]
+        output: Option<String>,
+        #[arg(short, long, help = "Overwrite existing files without asking")]
+        force: bool,
+        #[arg(
+            short,
+            long,
+            value_name = "N",
+            help = "Write up to N files at once [default: number of CPUs]"
+        )]
+        jobs: Option<NonZeroUsize>,
+        #[arg(
+            long,
+            help = "Append an extension derived from the fragment language to file names without one"
+        )]
+        add_extension: bool,
+        #[arg(
+            long,
+            help = "Download every fragment into one directory, dropping folders from file names"
+        )]
+        flatten: bool,
+        #[arg(
+            long,
+            conflicts_with_all = ["to", "pipe_to", "clipboard", "markdown", "stdout"],
+            help = "Read downloaded files back and compare their SHA-256 with the snippet"
+        )]
+        verify: bool,
+        #[arg(
+            long,
+            value_name = "N",
+            help = "Only preview or print (--stdout) the first N lines of each fragment"
+        )]
+        head: Option<usize>,
+        #[arg(
+            long,
+            value_name = "N",
+            conflicts_with = "head",
+            help = "Only preview or print (--stdout) the last N lines of each fragment"
+        )]
+        tail: Option<usize>,
+    },
+    #[command(about = "Add files to an existing snippet, keeping its current files")]
+    Append {
         #[arg(help = "Numeric snippet identifier")]
         id: usize,
+        #[arg(required = true, help = "Files to add")]
+        files: Vec<String>,
+        #[arg(long, help = "Allow uploading files that share the same file name")]
+        allow_duplicate_names: bool,
+        #[arg(
+            long,
+            help = "Upload even when the files exceed the maximum upload size"
+        )]
+        allow_large: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "skip",
+            help = "What to do with files that look binary"
+        )]
+        on_binary: OnBinary,
     },
     #[command(about = "Update an existing snippet")]
     Update {
-        #[arg(help = "Numeric snippet identifier")]
-        id: usize,
+        #[arg(
+            value_name = "ID",
+            help = "Numeric snippet identifier (omit to pick interactively; a non-numeric first argument is taken as a file)"
+        )]
+        id: Option<String>,
         #[arg(help = "Files to upload (replaces existing files)")]
         files: Vec<String>,
+        #[arg(long, help = "Allow uploading files that share the same file name")]
+        allow_duplicate_names: bool,
+        #[arg(
+            long,
+            help = "Upload even when the files exceed the maximum upload size"
+        )]
+        allow_large: bool,
+        #[arg(
+            long,
+            value_enum,
+            default_value = "skip",
+            help = "What to do with files that look binary"
+        )]
+        on_binary: OnBinary,
+        #[arg(long, help = "Check files for likely secrets before uploading")]
+        scan_secrets: bool,
+        #[arg(long, help = "New title; skips the title prompt")]
+        title: Option<String>,
+        #[arg(long, help = "New description; skips the description prompt")]
+        description: Option<String>,
+        #[arg(
+            long,
+            value_name = "LIST",
+            help = "New categories separated by the category separator; skips the categories prompt"
+        )]
+        categories: Option<String>,
+        #[arg(long, help = "Make the snippet public without prompting")]
+        public: bool,
+        #[arg(
+            long,
+            conflicts_with = "public",
+            help = "Make the snippet private without prompting"
+        )]
+        private: bool,
+        #[arg(
+            long,
+            help = "Keep the current title, description, visibility and categories without prompting"
+        )]
+        keep_metadata: bool,
+        #[arg(
+            long,
+            help = "Type categories instead of picking from the ones already in use (skips fetching them)"
+        )]
+        no_fetch: bool,
+        #[arg(
+            long,
+            value_name = "FILES",
+            value_delimiter = ',',
+            help = "Reorder the current files instead of uploading new ones: comma-separated file names, first shown first"
+        )]
+        order: Vec<String>,
+        #[arg(
+            short,
+            long,
+            help = "Apply the changes without asking after the preview"
+        )]
+        force: bool,
     },
-    #[command(about = "Delete a snippet by ID")]
+    #[command(about = "Delete snippets by ID")]
     Delete {
-        #[arg(help = "Numeric snippet identifier")]
-        id: usize,
+        #[arg(
+            help = "Numeric identifiers of the snippets to delete (omit to pick one interactively)"
+        )]
+        ids: Vec<usize>,
         #[arg(short, long, help = "Skip confirmation dialog")]
         force: bool,
     },
@@ -61,6 +596,93 @@ pub enum Commands {
         number: Option<usize>,
         #[arg(short = 'p', long, help = "Page number to display (starting at 1)")]
         page: Option<usize>,
+        #[arg(
+            long,
+            value_name = "TEXT",
+            help = "Highlight TEXT in titles and descriptions"
+        )]
+        highlight_query: Option<String>,
+        #[arg(
+            short,
+            long,
+            help = "Sort order: newest, oldest, alpha-asc, alpha-desc, language"
+        )]
+        sort: Option<String>,
+        #[arg(
+            long,
+            value_name = "DURATION",
+            value_parser = parse_window,
+            help = "Only show snippets updated within DURATION, e.g. 12h, 7d or 2w"
+        )]
+        updated_since: Option<RecentWindow>,
+        #[arg(
+            long = "category",
+            value_name = "NAME",
+            help = "Only show snippets in category NAME (repeatable, any of them matches)"
+        )]
+        categories: Vec<String>,
+        #[arg(
+            long,
+            requires = "categories",
+            help = "Only show snippets that have every --category"
+        )]
+        match_all: bool,
+        #[arg(long, help = "Show when each snippet was last updated")]
+        show_date: bool,
+        #[arg(long, help = "Show how often each snippet was shared")]
+        show_shares: bool,
+        #[arg(long, help = "Fetch the list from the server even if it is cached")]
+        refresh: bool,
+    },
+    #[command(
+        about = "Print the number of snippets",
+        long_about = "Print the number of snippets, optionally only those matching a search query or categories, as a bare integer for use in scripts."
+    )]
+    Count {
+        #[arg(long, help = "Only count snippets matching the search query")]
+        query: Option<String>,
+        #[arg(
+            long = "category",
+            value_name = "NAME",
+            help = "Only count snippets in category NAME (repeatable, any of them matches)"
+        )]
+        categories: Vec<String>,
+        #[arg(
+            long,
+            requires = "categories",
+            help = "Only count snippets that have every --category"
+        )]
+        match_all: bool,
+    },
+    #[command(
+        about = "Sync a snippet with a local directory",
+        long_about = "Compares each file in DIR with the snippet fragment of the same name, using the hashes recorded by the previous sync to tell which side changed. Changes are pulled and pushed accordingly; files changed on both sides are reported as conflicts."
+    )]
+    Sync {
+        #[arg(help = "Numeric snippet identifier")]
+        id: usize,
+        #[arg(help = "Directory mirroring the snippet files")]
+        dir: String,
+        #[arg(
+            long,
+            conflicts_with_all = ["push", "both"],
+            help = "Only download changes from the server"
+        )]
+        pull: bool,
+        #[arg(long, conflicts_with = "both", help = "Only upload local changes")]
+        push: bool,
+        #[arg(long, help = "Download and upload changes (default)")]
+        both: bool,
+    },
+    #[command(
+        about = "Show how local files differ from a snippet",
+        long_about = "Compares each local file with the snippet file of the same name and prints a unified diff. Local files missing from the snippet are reported as added, snippet files not given locally as removed, matching what `update` with the same files would do. Nothing is changed."
+    )]
+    Diff {
+        #[arg(help = "Numeric snippet identifier")]
+        id: usize,
+        #[arg(required = true, help = "Local files to compare")]
+        files: Vec<String>,
     },
     #[command(about = "Search snippets")]
     Search {
@@ -74,7 +696,351 @@ pub enum Commands {
         sort: Option<String>,
         #[arg(long, help = "Search within code fragments")]
         search_code: bool,
+        #[arg(
+            long,
+            help = "Treat the query as a regular expression, matched locally against all snippets"
+        )]
+        regex: bool,
+        #[arg(
+            short,
+            long,
+            requires = "regex",
+            help = "Match the --regex pattern case-insensitively"
+        )]
+        ignore_case: bool,
+        #[arg(
+            short,
+            long,
+            value_name = "LANG",
+            help = "Only show snippets with a file in this language (case-insensitive)"
+        )]
+        language: Option<String>,
+        #[arg(long, help = "Do not highlight the query in the results")]
+        no_highlight: bool,
+        #[arg(short = 'n', long, help = "Page size N")]
+        number: Option<usize>,
+        #[arg(short = 'p', long, help = "Page number to display (starting at 1)")]
+        page: Option<usize>,
+    },
+    #[cfg(feature = "gist")]
+    #[command(
+        about = "Publish a snippet as a GitHub gist",
+        long_about = "Fetches the snippet and creates a GitHub gist with one file per fragment. Requires a GitHub token with the gist scope in GITHUB_TOKEN."
+    )]
+    ExportGist {
+        #[arg(help = "Numeric snippet identifier")]
+        id: usize,
+        #[arg(long, help = "Create a public gist instead of a secret one")]
+        public: bool,
+    },
+    #[command(about = "Show which server this machine is logged in to (same as `config show`)")]
+    Whoami,
+    #[command(
+        about = "Summarize your snippet library",
+        long_about = "Show the number of snippets, files and shares, how many snippets use each category and how many files use each language."
+    )]
+    Stats,
+    #[command(
+        about = "List all categories with the number of snippets using them",
+        long_about = "Collects the categories of all snippets and prints each one with how many snippets use it, most used first. Categories differing only in case are counted together unless --case-sensitive is given."
+    )]
+    Categories,
+    #[command(
+        about = "Save all snippets to a local archive",
+        long_about = "Fetches every snippet with its files and writes them to a JSON file or a tar archive, keeping titles, descriptions, categories, visibility and files so they can be restored later."
+    )]
+    Backup {
+        #[arg(help = "Archive file to write")]
+        output: String,
+        #[arg(
+            long,
+            value_enum,
+            help = "Archive format [default: tar for .tar files, json otherwise]"
+        )]
+        format: Option<BackupFormat>,
+        #[arg(
+            long,
+            value_name = "SECS",
+            help = "Abort a single snippet after SECS seconds and continue with the next"
+        )]
+        timeout_per_file: Option<u64>,
+    },
+    #[command(
+        about = "Recreate snippets from a backup archive",
+        long_about = "Reads a JSON or tar archive written by `backup` and creates each snippet in it, with its files, on the current server. Snippets get new IDs.",
+        visible_alias = "restore"
+    )]
+    Import {
+        #[arg(help = "Archive written by `backup`")]
+        input: String,
+        #[arg(
+            long,
+            help = "Leave out snippets whose title already exists on the server"
+        )]
+        skip_existing: bool,
+        #[arg(
+            long,
+            help = "Skip snippets created by a previous run of the same import that failed partway"
+        )]
+        resume: bool,
+        #[arg(
+            long,
+            value_name = "SECS",
+            help = "Abort a single snippet after SECS seconds and continue with the next"
+        )]
+        timeout_per_file: Option<u64>,
+    },
+    #[command(
+        about = "Create a new snippet from a copy of an existing one",
+        visible_alias = "duplicate"
+    )]
+    Copy {
+        #[arg(help = "Numeric identifier of the snippet to copy")]
+        id: usize,
+        #[arg(
+            long,
+            help = "Title of the copy [default: the original title with \" (copy)\"]"
+        )]
+        title: Option<String>,
+    },
+    #[command(about = "Open a snippet in the ByteStash web UI")]
+    Open {
+        #[arg(help = "Numeric snippet identifier")]
+        id: usize,
+        #[arg(long, help = "Print the URL instead of opening a browser")]
+        print: bool,
+    },
+    /// Print `id<TAB>title` per snippet for shell completion; prints nothing on errors
+    #[command(name = "complete-ids", hide = true)]
+    CompleteIds,
+    #[command(
+        about = "Check that the server is reachable and accepts the API key",
+        long_about = "Sends one small authenticated request with a 5 second timeout and reports whether the server is unreachable, rejects the API key, or answered, with the response time.",
+        visible_alias = "health"
+    )]
+    Ping,
+    #[command(
+        about = "Detect which optional features the server supports",
+        long_about = "Sends read-only requests to the server, prints which optional features it supports and caches the result in the config file so other commands can adapt."
+    )]
+    Probe,
+    #[command(about = "Manage the stored API key")]
+    Key {
+        #[command(subcommand)]
+        command: KeyCommand,
     },
+    #[command(about = "Manage bytestashy settings")]
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommand,
+    },
+}
+
+impl Commands {
+    /// Whether the command can change snippets on the server, making a cached list stale
+    pub fn modifies_snippets(&self) -> bool {
+        matches!(
+            self,
+            Commands::Create { .. }
+                | Commands::Append { .. }
+                | Commands::Update { .. }
+                | Commands::Delete { .. }
+                | Commands::Sync { .. }
+                | Commands::Copy { .. }
+                | Commands::Import { .. }
+        )
+    }
+}
+
+/// Subcommands of `key`
+#[derive(Subcommand)]
+pub enum KeyCommand {
+    #[command(
+        about = "Create a new API key and replace the stored one",
+        long_about = "Creates a new API key using your credentials, verifies it and replaces the stored key. The stored key is left untouched if anything fails."
+    )]
+    Rotate {
+        #[arg(long, help = "Revoke the previous key on the server after rotating")]
+        revoke_old: bool,
+    },
+}
+
+/// Subcommands of `config`
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    #[command(
+        about = "Print the portable settings to stdout",
+        long_about = "Print the portable settings to stdout. The API key is never included."
+    )]
+    Export {
+        #[arg(long, value_enum, default_value = "json", help = "Output format")]
+        format: SettingsFormat,
+    },
+    #[command(
+        about = "Show the server, profile and config file in use",
+        long_about = "Show the server, profile and config file in use and whether an API key is stored. The key itself is never printed."
+    )]
+    Show,
+    #[command(
+        about = "Show every setting in effect and where it came from",
+        long_about = "Show every setting in effect with its source: a command-line flag, an environment variable, the config file, the keyring or the built-in default. Pass the same global flags as the command you are debugging. The API key and header values are redacted."
+    )]
+    Effective,
+    #[command(
+        about = "Convert config.json to config.toml",
+        long_about = "Writes the settings of config.json to config.toml in the same directory and renames the old file to config.json.bak. config.toml takes precedence from then on."
+    )]
+    Migrate,
+    #[command(about = "Merge settings from a JSON or TOML file into the current config")]
+    Import {
+        #[arg(help = "Settings file (.toml files are read as TOML, everything else as JSON)")]
+        file: String,
+    },
+}
+
+/// When destructive operations ask before going ahead
+#[derive(ValueEnum, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmPolicy {
+    /// Always ask, even when `--force` is given
+    Always,
+    /// Keep each command's own default
+    #[default]
+    Once,
+    /// Never ask, for trusted automation
+    Never,
+}
+
+impl std::fmt::Display for ConfirmPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConfirmPolicy::Always => write!(f, "always"),
+            ConfirmPolicy::Once => write!(f, "once"),
+            ConfirmPolicy::Never => write!(f, "never"),
+        }
+    }
+}
+
+/// When to color output
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ColorChoice {
+    /// Color terminals unless `NO_COLOR` is set
+    Auto,
+    Always,
+    Never,
+}
+
+/// Output shape of `list`, `get` and `search`
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutputFormat {
+    /// Colored layout for people
+    Table,
+    /// Uncolored, one snippet per line, for grep and friends
+    Plain,
+    Json,
+    Yaml,
+}
+
+impl OutputFormat {
+    /// Machine-readable formats print the data only, without prompts
+    pub fn is_structured(self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Yaml)
+    }
+}
+
+/// Policy for binary files passed to `create`
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OnBinary {
+    /// Leave the file out and warn
+    Skip,
+    /// Stop the whole upload
+    Abort,
+    /// Upload the file anyway
+    Include,
+}
+
+/// Archive shapes written by `backup`
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BackupFormat {
+    /// One JSON array of snippets
+    Json,
+    /// A tar archive with a directory per snippet
+    Tar,
+}
+
+/// Serialization formats for exported settings
+#[derive(ValueEnum, Clone, Copy)]
+pub enum SettingsFormat {
+    Json,
+    Toml,
+}
+
+/// Parse a byte size with an optional K/M/G (binary) suffix
+pub fn parse_size(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, multiplier) = match value.char_indices().last() {
+        Some((i, 'k' | 'K')) => (&value[..i], 1024),
+        Some((i, 'm' | 'M')) => (&value[..i], 1024 * 1024),
+        Some((i, 'g' | 'G')) => (&value[..i], 1024 * 1024 * 1024),
+        _ => (value, 1),
+    };
+    let n = digits
+        .parse::<u64>()
+        .map_err(|_| format!("invalid size '{value}', expected e.g. 512K or 64M"))?;
+    n.checked_mul(multiplier)
+        .ok_or_else(|| format!("size '{value}' too large"))
+}
+
+/// A recent time window such as `7d`, keeping its original spelling for display
+#[derive(Clone, Debug)]
+pub struct RecentWindow {
+    pub label: String,
+    pub duration: chrono::TimeDelta,
+}
+
+/// Parse a duration with an h (hours), d (days) or w (weeks) suffix
+pub fn parse_window(value: &str) -> Result<RecentWindow, String> {
+    let value = value.trim();
+    let error = || format!("invalid duration '{value}', expected e.g. 12h, 7d or 2w");
+    let (digits, unit) = match value.char_indices().last() {
+        Some((i, unit @ ('h' | 'd' | 'w'))) => (&value[..i], unit),
+        _ => return Err(error()),
+    };
+    let n = digits.parse::<i64>().map_err(|_| error())?;
+    let duration = match unit {
+        'h' => chrono::TimeDelta::try_hours(n),
+        'd' => chrono::TimeDelta::try_days(n),
+        _ => chrono::TimeDelta::try_weeks(n),
+    }
+    .ok_or_else(error)?;
+    Ok(RecentWindow {
+        label: value.to_string(),
+        duration,
+    })
+}
+
+/// Parse a `NAME=VALUE` template variable
+pub fn parse_var(value: &str) -> Result<(String, String), String> {
+    match value.split_once('=') {
+        Some((name, var_value)) if !name.trim().is_empty() => {
+            Ok((name.trim().to_string(), var_value.to_string()))
+        }
+        _ => Err(format!("invalid variable '{value}', expected NAME=VALUE")),
+    }
+}
+
+/// Parse a `Name: Value` header, validating both parts
+pub fn parse_header(value: &str) -> Result<(String, String), String> {
+    let (name, header_value) = value
+        .split_once(':')
+        .ok_or_else(|| format!("invalid header '{value}', expected 'Name: Value'"))?;
+    let name = name.trim();
+    let header_value = header_value.trim();
+    reqwest::header::HeaderName::from_bytes(name.as_bytes())
+        .map_err(|_| format!("invalid header name '{name}'"))?;
+    reqwest::header::HeaderValue::from_str(header_value)
+        .map_err(|_| format!("invalid value for header '{name}'"))?;
+    Ok((name.to_string(), header_value.to_string()))
 }
 
 /// Supported shell types for completion generation
@@ -0,0 +1,116 @@
+use crate::cli::{Cli, Shell};
+use clap::CommandFactory;
+use clap_complete::{generate, shells};
+
+/// Bash: complete IDs for the first argument of `get`/`update` and every
+/// argument of `delete`, falling back to the generated completion
+const BASH_IDS: &str = r#"
+_bytestashy_with_ids() {
+    local i command="" positional=0 cur="${COMP_WORDS[COMP_CWORD]}"
+    for (( i = 1; i < COMP_CWORD; i++ )); do
+        [[ ${COMP_WORDS[i]} == -* ]] && continue
+        if [[ -z $command ]]; then command=${COMP_WORDS[i]}; else positional=$((positional + 1)); fi
+    done
+    if [[ $cur != -* ]] && { [[ $command == get || $command == update ]] && (( positional == 0 )) || [[ $command == delete ]]; }; then
+        local ids
+        ids=$(bytestashy complete-ids 2>/dev/null | cut -f1)
+        COMPREPLY=($(compgen -W "$ids" -- "$cur"))
+        (( ${#COMPREPLY[@]} )) && return 0
+    fi
+    _bytestashy "$@"
+}
+
+if [[ "${BASH_VERSINFO[0]}" -eq 4 && "${BASH_VERSINFO[1]}" -ge 4 || "${BASH_VERSINFO[0]}" -gt 4 ]]; then
+    complete -F _bytestashy_with_ids -o nosort -o bashdefault -o default bytestashy
+else
+    complete -F _bytestashy_with_ids -o bashdefault -o default bytestashy
+fi
+"#;
+
+/// Zsh: same positions as bash, with titles as descriptions. Replaces the
+/// generated `_bytestashy`, which is kept as `_bytestashy_static`.
+const ZSH_IDS: &str = r#"_bytestashy() {
+    local i command positional=0
+    for (( i = 2; i < CURRENT; i++ )); do
+        [[ ${words[i]} == -* ]] && continue
+        if [[ -z $command ]]; then command=${words[i]}; else (( positional += 1 )); fi
+    done
+    if [[ ${words[CURRENT]} != -* ]] && { [[ $command == (get|update) ]] && (( positional == 0 )) || [[ $command == delete ]]; }; then
+        local -a ids
+        ids=(${(f)"$(bytestashy complete-ids 2>/dev/null)"})
+        ids=(${ids//:/\\:})
+        ids=(${ids/$'\t'/:})
+        _describe -t snippets 'snippet ID' ids && return 0
+    fi
+    _bytestashy_static "$@"
+}
+
+"#;
+
+/// Fish shows the title after the tab as the description
+const FISH_IDS: &str = r#"
+complete -c bytestashy -n "__fish_bytestashy_using_subcommand get; or __fish_bytestashy_using_subcommand delete" -f -a "(bytestashy complete-ids 2>/dev/null)"
+complete -c bytestashy -n "__fish_bytestashy_using_subcommand update" -a "(bytestashy complete-ids 2>/dev/null)"
+"#;
+
+/// Start of the dispatch block ending the generated zsh script
+const ZSH_DISPATCH: &str = "if [ \"$funcstack[1]\" = \"_bytestashy\" ]; then";
+const ZSH_FUNCTION: &str = "\n_bytestashy() {\n";
+
+/// Completion script for `shell`. Bash, zsh and fish scripts also complete
+/// snippet IDs by running `bytestashy complete-ids`.
+pub fn script(shell: &Shell) -> String {
+    let mut cmd = Cli::command();
+    let mut out = Vec::new();
+    match shell {
+        Shell::Bash => generate(shells::Bash, &mut cmd, "bytestashy", &mut out),
+        Shell::Zsh => generate(shells::Zsh, &mut cmd, "bytestashy", &mut out),
+        Shell::Fish => generate(shells::Fish, &mut cmd, "bytestashy", &mut out),
+        Shell::Powershell => generate(shells::PowerShell, &mut cmd, "bytestashy", &mut out),
+    }
+    let script = String::from_utf8_lossy(&out).into_owned();
+
+    match shell {
+        Shell::Bash => script + BASH_IDS,
+        Shell::Fish => script + FISH_IDS,
+        Shell::Zsh => with_zsh_ids(script),
+        Shell::Powershell => script,
+    }
+}
+
+/// Insert the zsh wrapper, leaving the script untouched if its layout is unexpected
+fn with_zsh_ids(script: String) -> String {
+    match (script.find(ZSH_FUNCTION), script.rfind(ZSH_DISPATCH)) {
+        (Some(_), Some(dispatch)) => {
+            let (body, tail) = script.split_at(dispatch);
+            let body = body.replacen(ZSH_FUNCTION, "\n_bytestashy_static() {\n", 1);
+            format!("{body}{ZSH_IDS}{tail}")
+        }
+        _ => script,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scripts_complete_snippet_ids() {
+        let bash = script(&Shell::Bash);
+        assert!(bash.contains("_bytestashy() {"));
+        assert!(bash.trim_end().ends_with("fi"));
+        assert!(bash.contains("complete -F _bytestashy_with_ids"));
+
+        let zsh = script(&Shell::Zsh);
+        assert!(zsh.contains("\n_bytestashy_static() {\n"));
+        let wrapper = zsh.find(ZSH_IDS).unwrap();
+        assert!(wrapper < zsh.rfind(ZSH_DISPATCH).unwrap());
+        assert_eq!(zsh.matches("\n_bytestashy() {").count(), 1);
+        assert!(zsh.starts_with("#compdef bytestashy"));
+
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            assert!(script(&shell).contains("bytestashy complete-ids"));
+        }
+        assert!(!script(&Shell::Powershell).contains("bytestashy complete-ids"));
+    }
+}
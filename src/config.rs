@@ -1,64 +1,271 @@
+use crate::cli::ConfirmPolicy;
+use crate::display::DescWidth;
+use crate::models::Capabilities;
 use directories::ProjectDirs;
 use keyring::Entry;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+use tracing::warn;
 
 /// Keyring service identifier
 const KEYRING_SERVICE: &str = "bytestashy";
 /// Keyring username for API key storage
 const KEYRING_USERNAME: &str = "api_key";
+/// Environment variable overriding the config file location
+pub const CONFIG_ENV: &str = "BYTESTASHY_CONFIG";
+/// Profile used when none is selected or configured
+pub const DEFAULT_PROFILE: &str = "default";
+/// Keys of the config file that imported settings can set
+pub const SETTING_KEYS: &[&str] = &[
+    "api_url",
+    "api_key_id",
+    "allow_public",
+    "max_response_bytes",
+    "max_upload_bytes",
+    "list_cache_secs",
+    "timeout_secs",
+    "max_retries",
+    "insecure",
+    "cacert",
+    "category_separator",
+    "prompt_history",
+    "secret_patterns",
+    "strict_secret_scan",
+    "desc_width",
+    "confirm_destructive",
+    "capabilities",
+    "extra_headers",
+    "default_profile",
+    "profiles",
+];
 
-/// Application configuration with API credentials
-#[derive(Serialize, Deserialize, Debug)]
+/// Connection settings of one named server
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Profile {
+    pub api_url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_id: Option<String>,
+}
+
+/// Application configuration with API credentials.
+/// `api_url`, `api_key` and `api_key_id` belong to the active profile; in the
+/// config file they are stored under `profiles`.
+#[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Config {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub api_url: String,
     /// API key stored in system keyring (not serialized)
     #[serde(skip)]
     pub api_key: String,
+    /// Server-side id of the stored API key, used to revoke it on rotation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_key_id: Option<String>,
+    /// Visibility policy: `Some(false)` forbids creating public snippets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allow_public: Option<bool>,
+    /// Maximum accepted API response size in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_response_bytes: Option<u64>,
+    /// Maximum total size of the files uploaded by one command, in bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_upload_bytes: Option<u64>,
+    /// Serve `list` from a local cache younger than this many seconds (0 disables it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub list_cache_secs: Option<u64>,
+    /// Timeout for a single HTTP request in seconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_secs: Option<u64>,
+    /// Retries for read requests failing with a connection error or 5xx response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_retries: Option<u32>,
+    /// Skip TLS certificate verification for the ByteStash server, like `--insecure`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub insecure: Option<bool>,
+    /// PEM CA certificate trusted for the ByteStash server, like `--cacert`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cacert: Option<PathBuf>,
+    /// Separator used when entering categories
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category_separator: Option<char>,
+    /// Remember prompt input across runs for up-arrow recall
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_history: Option<bool>,
+    /// Additional regexes treated as secrets by `--scan-secrets`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub secret_patterns: Vec<String>,
+    /// Abort instead of asking when `--scan-secrets` finds something
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub strict_secret_scan: Option<bool>,
+    /// Description length in snippet lists, a number or "auto"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc_width: Option<DescWidth>,
+    /// Default for `--confirm-destructive`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confirm_destructive: Option<ConfirmPolicy>,
+    /// Server features detected by `bytestashy probe`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub capabilities: Option<Capabilities>,
+    /// Extra headers sent with every request, e.g. for authenticating gateways
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extra_headers: BTreeMap<String, String>,
+    /// Profile used when `--profile` is not given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_profile: Option<String>,
+    /// Named servers, each with its own keyring entry
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, Profile>,
+    /// Name of the active profile (not serialized)
+    #[serde(skip)]
+    pub profile: Option<String>,
 }
 
 impl Config {
-    /// Load configuration from file and keyring
-    pub fn load() -> anyhow::Result<Option<Config>> {
-        if let Some(proj_dirs) = ProjectDirs::from("", "", "bytestashy") {
-            let config_path: PathBuf = proj_dirs.config_dir().join("config.json");
-            if config_path.exists() {
-                let content = fs::read_to_string(&config_path)?;
-                let mut cfg: Config = serde_json::from_str(&content)?;
-
-                // Load API key from keyring
-                match Self::get_api_key_from_keyring() {
-                    Ok(api_key) => {
-                        cfg.api_key = api_key;
-                    }
-                    Err(err) => {
-                        return Err(anyhow::anyhow!(
-                            "Error loading api key from keyring: {}",
-                            err
-                        ));
-                    }
+    /// Load configuration from file and keyring.
+    /// With a `keyring_timeout`, a keyring that does not answer in time (e.g. one
+    /// waiting for an unlock dialog) is reported as locked instead of blocking.
+    pub fn load(
+        profile: Option<&str>,
+        keyring_timeout: Option<Duration>,
+    ) -> anyhow::Result<Option<Config>> {
+        if let Some(mut cfg) = Self::load_file()? {
+            if let Some(name) = profile {
+                cfg.select_profile(name)?;
+            }
+            // Load API key from keyring
+            let username = keyring_username(cfg.profile_name());
+            match with_keyring_timeout(keyring_timeout, move || {
+                Self::get_api_key_from_keyring(&username)
+            }) {
+                Ok(api_key) => {
+                    cfg.api_key = api_key;
+                }
+                Err(err) => {
+                    return Err(anyhow::anyhow!(
+                        "Error loading api key from keyring: {}",
+                        err
+                    ));
                 }
+            }
+
+            return Ok(Some(cfg));
+        }
+        Ok(None)
+    }
+
+    /// Location of the config file: `BYTESTASHY_CONFIG` if set, else `config.toml`
+    /// in the platform config dir if it exists, else `config.json` there
+    pub fn path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os(CONFIG_ENV).filter(|path| !path.is_empty()) {
+            return Some(PathBuf::from(path));
+        }
+        let dir = ProjectDirs::from("", "", "bytestashy")?
+            .config_dir()
+            .to_path_buf();
+        let toml = dir.join("config.toml");
+        Some(if toml.exists() {
+            toml
+        } else {
+            dir.join("config.json")
+        })
+    }
 
-                return Ok(Some(cfg));
+    /// Load configuration file only, without touching the keyring.
+    /// The default profile is active; a single-server config from older
+    /// versions is migrated into it.
+    pub fn load_file() -> anyhow::Result<Option<Config>> {
+        if let Some(config_path) = Self::path()
+            && config_path.exists()
+        {
+            let content = fs::read_to_string(&config_path)?;
+            let (cfg, migrated) = Self::from_file_str(&content, is_toml(&config_path))?;
+            if migrated && let Err(err) = cfg.save_file() {
+                warn!("Could not migrate config to profiles: {}", err);
             }
+            return Ok(Some(cfg));
         }
         Ok(None)
     }
 
-    /// Save configuration to file and keyring
-    pub fn save(&self) -> anyhow::Result<()> {
-        Self::save_api_key_to_keyring(&self.api_key)?;
+    /// Parse the config file, reporting whether a legacy layout was migrated
+    fn from_file_str(content: &str, toml: bool) -> anyhow::Result<(Config, bool)> {
+        let mut cfg: Config = if toml {
+            toml::from_str(content)?
+        } else {
+            serde_json::from_str(content)?
+        };
+        let migrated = cfg.profiles.is_empty() && !cfg.api_url.is_empty();
+        if migrated {
+            cfg.profiles.insert(
+                DEFAULT_PROFILE.to_string(),
+                Profile {
+                    api_url: std::mem::take(&mut cfg.api_url),
+                    api_key_id: cfg.api_key_id.take(),
+                },
+            );
+        }
+        let name = cfg.profile_name().to_string();
+        if cfg.profiles.contains_key(&name) {
+            cfg.select_profile(&name)?;
+        }
+        Ok((cfg, migrated))
+    }
+
+    /// Name of the active profile
+    pub fn profile_name(&self) -> &str {
+        self.profile
+            .as_deref()
+            .or(self.default_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+    }
 
-        if let Some(proj_dirs) = ProjectDirs::from("", "", "bytestashy") {
-            let config_dir = proj_dirs.config_dir();
-            fs::create_dir_all(config_dir)?;
-            let config_path = config_dir.join("config.json");
-            let mut file = fs::File::create(&config_path)?;
+    /// Make an existing profile the active one
+    pub fn select_profile(&mut self, name: &str) -> anyhow::Result<()> {
+        let Some(profile) = self.profiles.get(name).cloned() else {
+            anyhow::bail!(
+                "Unknown profile '{name}'. Create it with `bytestashy login --profile {name} <api-url>`."
+            );
+        };
+        self.profile = Some(name.to_string());
+        self.api_url = profile.api_url;
+        self.api_key_id = profile.api_key_id;
+        Ok(())
+    }
+
+    /// Switch to the profile a login writes to, creating it on save.
+    /// The first profile created becomes the default.
+    pub fn prepare_login(&mut self, profile: Option<&str>) {
+        let name = profile.unwrap_or(self.profile_name()).to_string();
+        if self.profiles.is_empty() && self.default_profile.is_none() && name != DEFAULT_PROFILE {
+            self.default_profile = Some(name.clone());
+        }
+        self.profile = Some(name);
+        self.api_key_id = None;
+    }
+
+    /// Save configuration to file and keyring, see [`Config::load`] for `keyring_timeout`
+    pub fn save(&self, keyring_timeout: Option<Duration>) -> anyhow::Result<()> {
+        let api_key = self.api_key.clone();
+        let username = keyring_username(self.profile_name());
+        with_keyring_timeout(keyring_timeout, move || {
+            Self::save_api_key_to_keyring(&username, &api_key)
+        })?;
+        self.save_file()
+    }
 
-            let json = serde_json::to_string_pretty(self)?;
-            file.write_all(json.as_bytes())?;
+    /// Save configuration file only, leaving the keyring untouched
+    pub fn save_file(&self) -> anyhow::Result<()> {
+        if let Some(config_path) = Self::path() {
+            if let Some(config_dir) = config_path.parent() {
+                fs::create_dir_all(config_dir)?;
+            }
+            let mut file = fs::File::create(&config_path)?;
+            file.write_all(self.to_file_string(is_toml(&config_path))?.as_bytes())?;
 
             // Set restrictive permissions on Unix systems
             #[cfg(unix)]
@@ -74,20 +281,172 @@ impl Config {
         }
     }
 
+    /// File layout: the active connection is stored in `profiles`, not at the top level
+    fn to_file_value(&self) -> anyhow::Result<serde_json::Value> {
+        let mut value = serde_json::to_value(self)?;
+        let mut profiles = self.profiles.clone();
+        if !self.api_url.is_empty() {
+            profiles.insert(
+                self.profile_name().to_string(),
+                Profile {
+                    api_url: self.api_url.clone(),
+                    api_key_id: self.api_key_id.clone(),
+                },
+            );
+        }
+        if let Some(map) = value.as_object_mut() {
+            map.remove("api_url");
+            map.remove("api_key_id");
+            if !profiles.is_empty() {
+                map.insert("profiles".to_string(), serde_json::to_value(profiles)?);
+            }
+        }
+        Ok(value)
+    }
+
+    /// Contents of the config file in JSON or TOML
+    fn to_file_string(&self, toml: bool) -> anyhow::Result<String> {
+        let mut value = self.to_file_value()?;
+        if toml {
+            // TOML has no null; unset fields are simply left out
+            strip_nulls(&mut value);
+            Ok(toml::to_string_pretty(&value)?)
+        } else {
+            Ok(serde_json::to_string_pretty(&value)?)
+        }
+    }
+
+    /// Convert the JSON config file to `config.toml` beside it, keeping the
+    /// old file as `config.json.bak`. Returns the old and new paths, and the
+    /// backup path. When `BYTESTASHY_CONFIG` names the JSON file it is left in
+    /// place, since moving it would break every run until the variable is updated.
+    pub fn migrate_to_toml() -> anyhow::Result<(PathBuf, PathBuf, Option<PathBuf>)> {
+        let Some(from) = Self::path() else {
+            anyhow::bail!("Could not determine the config file location");
+        };
+        if is_toml(&from) {
+            anyhow::bail!("{} is already a TOML file", from.display());
+        }
+        if !from.exists() {
+            anyhow::bail!("No config file at {}", from.display());
+        }
+        let to = from.with_extension("toml");
+        if to.exists() {
+            anyhow::bail!("{} already exists", to.display());
+        }
+
+        let (cfg, _) = Self::from_file_str(&fs::read_to_string(&from)?, false)?;
+        let mut file = fs::File::create(&to)?;
+        file.write_all(cfg.to_file_string(true)?.as_bytes())?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&to, fs::Permissions::from_mode(0o600))?;
+        }
+        if std::env::var_os(CONFIG_ENV).is_some_and(|path| !path.is_empty()) {
+            return Ok((from, to, None));
+        }
+        let backup = from.with_extension("json.bak");
+        fs::rename(&from, &backup)?;
+        Ok((from, to, Some(backup)))
+    }
+
+    /// Parse portable settings from JSON or TOML text
+    pub fn parse_settings(content: &str, toml: bool) -> anyhow::Result<serde_json::Value> {
+        let value = if toml {
+            toml::from_str::<serde_json::Value>(content)?
+        } else {
+            serde_json::from_str(content)?
+        };
+        if !value.is_object() {
+            anyhow::bail!("Settings must be a JSON object or TOML table");
+        }
+        Ok(value)
+    }
+
+    /// Return a copy of this config with the given settings merged on top.
+    /// Only `SETTING_KEYS` are taken; credentials are never imported.
+    pub fn merged_with(&self, settings: serde_json::Value) -> anyhow::Result<Config> {
+        let mut merged = serde_json::to_value(self)?;
+        if let (Some(target), serde_json::Value::Object(source)) =
+            (merged.as_object_mut(), settings)
+        {
+            for (key, value) in source {
+                if SETTING_KEYS.contains(&key.as_str()) {
+                    target.insert(key, value);
+                }
+            }
+        }
+        let mut cfg: Config = serde_json::from_value(merged)?;
+        cfg.api_key = self.api_key.clone();
+        cfg.profile = self.profile.clone();
+        Ok(cfg)
+    }
+
     /// Store API key securely in system keyring
-    fn save_api_key_to_keyring(api_key: &str) -> anyhow::Result<()> {
-        let entry = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME)?;
+    fn save_api_key_to_keyring(username: &str, api_key: &str) -> anyhow::Result<()> {
+        let entry = Entry::new(KEYRING_SERVICE, username)?;
         entry.set_password(api_key)?;
         Ok(())
     }
 
     /// Retrieve API key from system keyring
-    fn get_api_key_from_keyring() -> anyhow::Result<String> {
-        let entry = Entry::new(KEYRING_SERVICE, KEYRING_USERNAME)?;
+    fn get_api_key_from_keyring(username: &str) -> anyhow::Result<String> {
+        let entry = Entry::new(KEYRING_SERVICE, username)?;
         Ok(entry.get_password()?)
     }
 }
 
+/// Whether a config file is TOML rather than JSON, judged by its extension
+pub fn is_toml(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("toml"))
+}
+
+/// Remove null values, which TOML cannot represent
+fn strip_nulls(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.retain(|_, v| !v.is_null());
+            map.values_mut().for_each(strip_nulls);
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(strip_nulls),
+        _ => {}
+    }
+}
+
+/// Keyring entry of a profile; the default profile keeps the entry of
+/// single-server versions so existing logins survive the migration
+fn keyring_username(profile: &str) -> String {
+    if profile == DEFAULT_PROFILE {
+        KEYRING_USERNAME.to_string()
+    } else {
+        format!("{KEYRING_USERNAME}:{profile}")
+    }
+}
+
+/// Run a keyring operation, giving up after `timeout` if one is set.
+/// The keyring crate offers no non-interactive mode, so a locked keyring that
+/// shows an unlock prompt is detected by the call not returning in time.
+fn with_keyring_timeout<T: Send + 'static>(
+    timeout: Option<Duration>,
+    op: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    let Some(timeout) = timeout else {
+        return op();
+    };
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = sender.send(op());
+    });
+    receiver.recv_timeout(timeout).unwrap_or_else(|_| {
+        Err(anyhow::anyhow!(
+            "keyring locked or not responding after {}s; unlock it or run without --no-keyring-prompt",
+            timeout.as_secs()
+        ))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,6 +456,7 @@ mod tests {
         let config = Config {
             api_url: "https://example.com".to_string(),
             api_key: "test-key".to_string(),
+            ..Default::default()
         };
 
         assert_eq!(config.api_url, "https://example.com");
@@ -108,6 +468,7 @@ mod tests {
         let config = Config {
             api_url: "https://example.com".to_string(),
             api_key: "test-key".to_string(), // This should be skipped in serialization
+            ..Default::default()
         };
 
         let json = serde_json::to_string(&config).unwrap();
@@ -125,5 +486,181 @@ mod tests {
 
         assert_eq!(config.api_url, "https://example.com");
         assert_eq!(config.api_key, ""); // Default empty string for skipped field
+        assert_eq!(config.allow_public, None);
+    }
+
+    #[test]
+    fn test_config_merge_settings_ignores_api_key() {
+        let config = Config {
+            api_url: "https://old.example.com".to_string(),
+            allow_public: Some(true),
+            ..Default::default()
+        };
+        let settings = Config::parse_settings(
+            "api_url = \"https://new.example.com\"\napi_key = \"leak\"\ncategory_separator = \";\"\n",
+            true,
+        )
+        .unwrap();
+
+        let merged = config.merged_with(settings).unwrap();
+
+        assert_eq!(merged.api_url, "https://new.example.com");
+        assert_eq!(merged.allow_public, Some(true));
+        assert_eq!(merged.category_separator, Some(';'));
+        assert_eq!(merged.api_key, "");
+    }
+
+    #[test]
+    fn test_setting_keys_cover_every_field() {
+        let config = Config {
+            api_url: "https://example.com".to_string(),
+            api_key: "secret".to_string(),
+            api_key_id: Some("1".to_string()),
+            allow_public: Some(true),
+            max_response_bytes: Some(1),
+            max_upload_bytes: Some(1),
+            list_cache_secs: Some(1),
+            timeout_secs: Some(1),
+            max_retries: Some(1),
+            insecure: Some(false),
+            cacert: Some(PathBuf::from("ca.pem")),
+            category_separator: Some(';'),
+            prompt_history: Some(true),
+            secret_patterns: vec!["x".to_string()],
+            strict_secret_scan: Some(true),
+            desc_width: Some(DescWidth::Auto),
+            confirm_destructive: Some(ConfirmPolicy::Always),
+            capabilities: Some(Capabilities::default()),
+            extra_headers: BTreeMap::from([("X-A".to_string(), "b".to_string())]),
+            default_profile: Some("work".to_string()),
+            profiles: BTreeMap::from([("work".to_string(), Profile::default())]),
+            profile: Some("work".to_string()),
+        };
+
+        let value = serde_json::to_value(&config).unwrap();
+        let mut keys: Vec<&str> = value
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        let mut expected = SETTING_KEYS.to_vec();
+        keys.sort_unstable();
+        expected.sort_unstable();
+        assert_eq!(keys, expected);
+    }
+
+    #[test]
+    fn test_config_allow_public_policy() {
+        let json = r#"{"api_url":"https://example.com","allow_public":false}"#;
+        let config: Config = serde_json::from_str(json).unwrap();
+
+        assert_eq!(config.allow_public, Some(false));
+    }
+
+    #[test]
+    fn test_config_caches_capabilities() {
+        let config = Config {
+            api_url: "https://example.com".to_string(),
+            capabilities: Some(Capabilities {
+                search_code: Some(true),
+                trash: Some(false),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        let json = serde_json::to_string(&config).unwrap();
+        let loaded: Config = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(loaded.capabilities, config.capabilities);
+    }
+
+    #[test]
+    fn test_keyring_timeout_fails_fast() {
+        let result = with_keyring_timeout(Some(Duration::from_millis(10)), || {
+            thread::sleep(Duration::from_secs(5));
+            Ok(())
+        });
+        assert!(result.unwrap_err().to_string().contains("keyring locked"));
+
+        let result = with_keyring_timeout(Some(Duration::from_secs(5)), || Ok(42));
+        assert_eq!(result.unwrap(), 42);
+    }
+
+    #[test]
+    fn test_legacy_config_migrates_to_default_profile() {
+        let (config, migrated) = Config::from_file_str(
+            r#"{"api_url":"https://example.com","api_key_id":"7","allow_public":false}"#,
+            false,
+        )
+        .unwrap();
+
+        assert!(migrated);
+        assert_eq!(config.profile_name(), DEFAULT_PROFILE);
+        assert_eq!(config.api_url, "https://example.com");
+        assert_eq!(config.api_key_id.as_deref(), Some("7"));
+
+        let value = config.to_file_value().unwrap();
+        assert!(value.get("api_url").is_none());
+        assert_eq!(
+            value["profiles"]["default"]["api_url"],
+            "https://example.com"
+        );
+        assert_eq!(value["allow_public"], false);
+    }
+
+    #[test]
+    fn test_profiles_select_and_login() {
+        let (mut config, migrated) = Config::from_file_str(
+            r#"{"default_profile":"work","profiles":{"work":{"api_url":"https://work.example"},"home":{"api_url":"https://home.example"}}}"#,
+            false,
+        )
+        .unwrap();
+        assert!(!migrated);
+        assert_eq!(config.api_url, "https://work.example");
+
+        config.select_profile("home").unwrap();
+        assert_eq!(config.api_url, "https://home.example");
+        assert!(config.select_profile("missing").is_err());
+
+        config.prepare_login(Some("lab"));
+        config.api_url = "https://lab.example".to_string();
+        let value = config.to_file_value().unwrap();
+        assert_eq!(value["profiles"]["lab"]["api_url"], "https://lab.example");
+        assert_eq!(value["profiles"]["home"]["api_url"], "https://home.example");
+        assert_eq!(value["default_profile"], "work");
+
+        assert_eq!(keyring_username("default"), "api_key");
+        assert_eq!(keyring_username("lab"), "api_key:lab");
+    }
+
+    #[test]
+    fn test_config_file_round_trips_through_json_and_toml() {
+        let config = Config {
+            api_url: "https://example.com".to_string(),
+            api_key: "test-key".to_string(),
+            allow_public: Some(false),
+            capabilities: Some(Capabilities {
+                search_code: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        for toml in [false, true] {
+            let content = config.to_file_string(toml).unwrap();
+            assert!(!content.contains("api_key"));
+            assert!(!content.contains("test-key"));
+
+            let (restored, migrated) = Config::from_file_str(&content, toml).unwrap();
+            assert!(!migrated);
+            assert_eq!(restored.api_url, "https://example.com");
+            assert_eq!(restored.allow_public, Some(false));
+            assert_eq!(restored.capabilities, config.capabilities);
+            assert_eq!(restored.api_key, "");
+        }
+        assert!(is_toml(Path::new("config.TOML")));
+        assert!(!is_toml(Path::new("config.json")));
     }
 }
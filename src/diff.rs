@@ -0,0 +1,141 @@
+use colored::*;
+use similar::{ChangeTag, TextDiff};
+use std::fmt;
+
+/// Render a colored unified diff between two texts, like `git diff`
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let mut out = String::new();
+
+    out.push_str(&format!("{}\n", format!("--- {old_label}").bold()));
+    out.push_str(&format!("{}\n", format!("+++ {new_label}").bold()));
+    for hunk in diff.unified_diff().context_radius(3).iter_hunks() {
+        out.push_str(&format!("{}\n", hunk.header().to_string().cyan()));
+        for change in hunk.iter_changes() {
+            let line = format!("{}{}", change.tag(), change.value());
+            let line = line.trim_end_matches('\n');
+            let styled = match change.tag() {
+                ChangeTag::Delete => line.red(),
+                ChangeTag::Insert => line.green(),
+                ChangeTag::Equal => line.normal(),
+            };
+            out.push_str(&format!("{styled}\n"));
+        }
+    }
+
+    out
+}
+
+/// Counts of files changed between a snippet and local files
+#[derive(Debug, Default, PartialEq)]
+pub struct ChangeSummary {
+    pub modified: usize,
+    pub added: usize,
+    pub removed: usize,
+    pub unchanged: usize,
+}
+
+impl ChangeSummary {
+    /// Whether both sides hold the same files with the same contents
+    pub fn is_empty(&self) -> bool {
+        self.modified + self.added + self.removed == 0
+    }
+}
+
+impl fmt::Display for ChangeSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} modified, {} added, {} removed, {} unchanged",
+            self.modified, self.added, self.removed, self.unchanged
+        )
+    }
+}
+
+/// Diff snippet files against local files, matched by name as `(name, code)`
+/// pairs. Files only present locally are reported as added, files only in the
+/// snippet as removed.
+pub fn compare_files(snippet: &[(&str, &str)], local: &[(&str, &str)]) -> (String, ChangeSummary) {
+    let mut out = String::new();
+    let mut summary = ChangeSummary::default();
+
+    for (name, old) in snippet {
+        match local.iter().find(|(local_name, _)| local_name == name) {
+            Some((_, new)) if old == new => summary.unchanged += 1,
+            Some((_, new)) => {
+                summary.modified += 1;
+                out.push_str(&unified_diff(
+                    old,
+                    new,
+                    &format!("snippet/{name}"),
+                    &format!("local/{name}"),
+                ));
+            }
+            None => {
+                summary.removed += 1;
+                out.push_str(&format!("{}\n", format!("removed: {name}").red().bold()));
+            }
+        }
+    }
+    for (name, _) in local {
+        if !snippet.iter().any(|(snippet_name, _)| snippet_name == name) {
+            summary.added += 1;
+            out.push_str(&format!("{}\n", format!("added: {name}").green().bold()));
+        }
+    }
+
+    (out, summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_diff_marks_changes() {
+        let out = unified_diff("a\nb\nc\n", "a\nB\nc\n", "snippet", "local");
+        let out = console::strip_ansi_codes(&out);
+
+        assert!(out.contains("--- snippet"));
+        assert!(out.contains("+++ local"));
+        assert!(out.contains("-b"));
+        assert!(out.contains("+B"));
+        assert!(out.contains(" a"));
+    }
+
+    #[test]
+    fn test_unified_diff_identical_has_no_hunks() {
+        let out = unified_diff("same\n", "same\n", "a", "b");
+
+        assert!(!out.contains("@@"));
+    }
+
+    #[test]
+    fn test_compare_files_reports_added_and_removed() {
+        let (out, summary) = compare_files(
+            &[("a.rs", "one\n"), ("b.rs", "same\n"), ("old.rs", "x\n")],
+            &[("a.rs", "two\n"), ("b.rs", "same\n"), ("new.rs", "y\n")],
+        );
+        let out = console::strip_ansi_codes(&out);
+
+        assert_eq!(
+            summary,
+            ChangeSummary {
+                modified: 1,
+                added: 1,
+                removed: 1,
+                unchanged: 1,
+            }
+        );
+        assert!(!summary.is_empty());
+        assert!(out.contains("--- snippet/a.rs\n+++ local/a.rs\n"));
+        assert!(out.contains("-one\n+two\n"));
+        assert!(out.contains("removed: old.rs\n"));
+        assert!(out.contains("added: new.rs\n"));
+        assert!(!out.contains("b.rs"));
+        assert_eq!(
+            summary.to_string(),
+            "1 modified, 1 added, 1 removed, 1 unchanged"
+        );
+    }
+}
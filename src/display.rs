@@ -0,0 +1,592 @@
+use crate::cli::ColorChoice;
+use crate::models::Snippet;
+use chrono::{DateTime, Utc};
+use colored::*;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::io::IsTerminal;
+use std::str::FromStr;
+
+/// Whether to color output. `NO_COLOR` (any non-empty value) only affects `auto`.
+pub fn colors_enabled(choice: ColorChoice, no_color: Option<&str>, is_terminal: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => no_color.is_none_or(str::is_empty) && is_terminal,
+    }
+}
+
+/// Layout of JSON written to stdout
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// Pretty for terminals, compact for pipes
+    Auto,
+    Pretty,
+    Compact,
+}
+
+impl JsonStyle {
+    pub fn from_flags(pretty: bool, compact: bool) -> JsonStyle {
+        match (pretty, compact) {
+            (true, _) => JsonStyle::Pretty,
+            (_, true) => JsonStyle::Compact,
+            _ => JsonStyle::Auto,
+        }
+    }
+}
+
+/// Serialize a value as JSON in the requested style
+pub fn to_json<T: Serialize>(value: &T, style: JsonStyle) -> serde_json::Result<String> {
+    let pretty = match style {
+        JsonStyle::Auto => std::io::stdout().is_terminal(),
+        JsonStyle::Pretty => true,
+        JsonStyle::Compact => false,
+    };
+    if pretty {
+        serde_json::to_string_pretty(value)
+    } else {
+        serde_json::to_string(value)
+    }
+}
+
+/// Print a value as JSON to stdout; the shared path for all JSON output
+pub fn print_json<T: Serialize>(value: &T, style: JsonStyle) -> serde_json::Result<()> {
+    println!("{}", to_json(value, style)?);
+    Ok(())
+}
+
+/// Print a value as YAML to stdout
+pub fn print_yaml<T: Serialize>(value: &T) -> serde_yaml::Result<()> {
+    print!("{}", serde_yaml::to_string(value)?);
+    Ok(())
+}
+
+/// Description length used when nothing else is configured
+pub const DEFAULT_DESC_WIDTH: usize = 60;
+
+/// How many description characters to show in snippet lists
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DescWidth {
+    Fixed(usize),
+    /// Fill the rest of the terminal line
+    Auto,
+}
+
+impl FromStr for DescWidth {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.eq_ignore_ascii_case("auto") {
+            return Ok(DescWidth::Auto);
+        }
+        value
+            .parse()
+            .map(DescWidth::Fixed)
+            .map_err(|_| format!("invalid width '{value}', expected a number or 'auto'"))
+    }
+}
+
+// Stored in the config as a number or "auto"
+impl fmt::Display for DescWidth {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DescWidth::Fixed(width) => write!(f, "{width}"),
+            DescWidth::Auto => write!(f, "auto"),
+        }
+    }
+}
+
+impl Serialize for DescWidth {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            DescWidth::Fixed(width) => serializer.serialize_u64(*width as u64),
+            DescWidth::Auto => serializer.serialize_str("auto"),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for DescWidth {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Raw {
+            Number(usize),
+            Text(String),
+        }
+        match Raw::deserialize(deserializer)? {
+            Raw::Number(width) => Ok(DescWidth::Fixed(width)),
+            Raw::Text(text) => text.parse().map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+impl DescWidth {
+    /// Resolve to a character count for the given snippets. `Auto` uses the
+    /// terminal width minus the id and longest title columns.
+    pub fn resolve(self, snippets: &[Snippet]) -> usize {
+        match self {
+            DescWidth::Fixed(width) => width,
+            DescWidth::Auto => {
+                let Some((_, columns)) = console::Term::stdout().size_checked() else {
+                    return DEFAULT_DESC_WIDTH;
+                };
+                let title = snippets
+                    .iter()
+                    .map(|snip| snip.title.chars().count())
+                    .max()
+                    .unwrap_or(0);
+                // "[ ID] " + title + " (" + description + "…)"
+                (columns as usize).saturating_sub(6 + title + 4).max(10)
+            }
+        }
+    }
+}
+
+/// One page of a result list, as selected by `--number` and `--page`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Page {
+    /// 1-based page number
+    pub index: usize,
+    pub size: usize,
+    pub total: usize,
+}
+
+impl Page {
+    /// Page `page` (default 1) of `number` items (default 10) out of `total`
+    pub fn new(number: Option<usize>, page: Option<usize>, total: usize) -> Page {
+        Page {
+            index: page.unwrap_or(1).max(1),
+            size: number.unwrap_or(10).min(total).max(1),
+            total,
+        }
+    }
+
+    pub fn offset(&self) -> usize {
+        (self.index - 1) * self.size
+    }
+
+    pub fn num_pages(&self) -> usize {
+        self.total.saturating_sub(1) / self.size + 1
+    }
+
+    /// `page: 2/5 - total snippets: 42` line, with `suffix` appended
+    pub fn footer(&self, suffix: &str) -> String {
+        format!(
+            "{}{}/{}{}{}{suffix}\n",
+            "page: ".white(),
+            self.index.to_string().bright_yellow().bold(),
+            self.num_pages().to_string().bright_yellow().bold(),
+            " - total snippets: ".white(),
+            self.total.to_string().bright_yellow().bold(),
+        )
+    }
+}
+
+/// Options controlling how snippet lists are rendered
+pub struct ListView<'a> {
+    /// Text to highlight within titles and descriptions
+    pub highlight: Option<&'a str>,
+    /// Pattern to highlight instead of the literal `highlight`
+    pub highlight_pattern: Option<&'a Regex>,
+    /// Also show the first code line matching the highlight
+    pub code_matches: bool,
+    /// Descriptions longer than this many characters are truncated
+    pub desc_width: usize,
+    /// Append when the snippet was last updated
+    pub show_date: bool,
+    /// Append how often the snippet was shared
+    pub show_shares: bool,
+}
+
+impl Default for ListView<'_> {
+    fn default() -> Self {
+        ListView {
+            highlight: None,
+            highlight_pattern: None,
+            code_matches: false,
+            desc_width: DEFAULT_DESC_WIDTH,
+            show_date: false,
+            show_shares: false,
+        }
+    }
+}
+
+/// Part of a fragment to show in previews
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineLimit {
+    Head(usize),
+    Tail(usize),
+}
+
+impl LineLimit {
+    /// Start and end of the kept lines out of `total`
+    fn bounds(self, total: usize) -> (usize, usize) {
+        match self {
+            LineLimit::Head(n) => (0, n.min(total)),
+            LineLimit::Tail(n) => (total.saturating_sub(n), total),
+        }
+    }
+}
+
+/// Options controlling how fragment code is rendered in previews
+#[derive(Default, Clone, Copy)]
+pub struct CodeView {
+    pub line_numbers: bool,
+    pub limit: Option<LineLimit>,
+}
+
+/// Build a case-insensitive matcher for a literal query
+fn query_matcher(query: &str) -> Option<Regex> {
+    if query.is_empty() {
+        return None;
+    }
+    RegexBuilder::new(&regex::escape(query))
+        .case_insensitive(true)
+        .build()
+        .ok()
+}
+
+/// Style `text` with `base`, marking every match of `matcher` with a highlight
+fn highlight_matches(
+    text: &str,
+    matcher: Option<&Regex>,
+    base: impl Fn(&str) -> ColoredString,
+) -> String {
+    let Some(matcher) = matcher else {
+        return base(text).to_string();
+    };
+
+    let mut out = String::new();
+    let mut last = 0;
+    for m in matcher.find_iter(text) {
+        if m.start() > last {
+            out.push_str(&base(&text[last..m.start()]).to_string());
+        }
+        out.push_str(&base(m.as_str()).black().on_yellow().to_string());
+        last = m.end();
+    }
+    if last < text.len() {
+        out.push_str(&base(&text[last..]).to_string());
+    }
+    out
+}
+
+/// Render formatted list of snippets with truncated descriptions
+pub fn render_snippets_list(snippets: &[Snippet], view: &ListView) -> String {
+    let matcher = view
+        .highlight_pattern
+        .cloned()
+        .or_else(|| view.highlight.and_then(query_matcher));
+    let now = Utc::now();
+    let mut out = String::new();
+
+    out.push_str(&format!(
+        "{}\n",
+        "[ ID] TITLE (DESCRIPTION)".underline().bold()
+    ));
+    for snip in snippets {
+        // Limit description length for display
+        let desc = {
+            let d = &snip.description;
+            if d.chars().count() > view.desc_width {
+                d.chars().take(view.desc_width).collect::<String>() + "…"
+            } else {
+                d.clone()
+            }
+        };
+        let c_desc = if desc.is_empty() {
+            String::new()
+        } else {
+            highlight_matches(&format!("({desc})"), matcher.as_ref(), |s| s.white())
+        };
+        let c_title = highlight_matches(&snip.title, matcher.as_ref(), |s| s.bold());
+        let c_id = snip.id.to_string().bright_purple();
+        let mut columns = Vec::new();
+        if view.show_date {
+            columns.push(match snip.updated_at_time() {
+                Some(time) => relative_time(time, now),
+                None => snip.updated_at.clone(),
+            });
+        }
+        if view.show_shares {
+            columns.push(match snip.share_count {
+                1 => "1 share".to_string(),
+                n => format!("{n} shares"),
+            });
+        }
+        let c_columns = if columns.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", columns.join(" · ").dimmed())
+        };
+        out.push_str(&format!("[{c_id:>3}] {c_title} {c_desc}{c_columns}\n"));
+
+        if view.code_matches
+            && let Some(matcher) = &matcher
+        {
+            let first_match = snip.fragments.iter().find_map(|fragment| {
+                fragment
+                    .code
+                    .lines()
+                    .enumerate()
+                    .find(|(_, line)| matcher.is_match(line))
+                    .map(|(i, line)| (fragment, i + 1, line))
+            });
+            if let Some((fragment, line_number, line)) = first_match {
+                out.push_str(&format!(
+                    "      {} {}\n",
+                    format!("{}:{line_number}:", fragment.file_name).dimmed(),
+                    highlight_matches(line.trim(), Some(matcher), |s| s.normal())
+                ));
+            }
+        }
+    }
+    out
+}
+
+/// "3 days ago" style age of `time`; times older than a month show their date
+pub fn relative_time(time: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let age = now.signed_duration_since(time);
+    let ago = |n: i64, unit: &str| format!("{n} {unit}{} ago", if n == 1 { "" } else { "s" });
+    match age {
+        age if age.num_minutes() < 1 => "just now".to_string(),
+        age if age.num_hours() < 1 => ago(age.num_minutes(), "minute"),
+        age if age.num_days() < 1 => ago(age.num_hours(), "hour"),
+        age if age.num_days() <= 30 => ago(age.num_days(), "day"),
+        _ => time.format("%Y-%m-%d").to_string(),
+    }
+}
+
+/// Snippet list for `--output-format plain`: `ID<TAB>TITLE<TAB>DESCRIPTION`
+/// per line, without header, colors or truncation
+pub fn render_snippets_plain(snippets: &[Snippet]) -> String {
+    snippets
+        .iter()
+        .map(|snip| {
+            let one_line = |text: &str| text.replace(['\t', '\n', '\r'], " ");
+            format!(
+                "{}\t{}\t{}\n",
+                snip.id,
+                one_line(&snip.title),
+                one_line(&snip.description)
+            )
+        })
+        .collect()
+}
+
+/// Render fragment code for terminal preview
+pub fn render_code(code: &str, view: CodeView) -> String {
+    if !view.line_numbers && view.limit.is_none() {
+        return code.to_string();
+    }
+
+    let lines: Vec<&str> = code.lines().collect();
+    let total = lines.len();
+    let (start, end) = view.limit.map_or((0, total), |limit| limit.bounds(total));
+
+    // Width of the gutter is derived from the last line number of this fragment
+    let width = total.max(1).to_string().len();
+    let mut shown: Vec<String> = lines[start..end]
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            if view.line_numbers {
+                let number = format!("{:>width$}", start + i + 1);
+                format!("{} {line}", number.dimmed())
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    let hidden = total - (end - start);
+    if hidden > 0 {
+        let marker = format!("… ({hidden} more lines)").dimmed().to_string();
+        match view.limit {
+            Some(LineLimit::Tail(_)) => shown.insert(0, marker),
+            _ => shown.push(marker),
+        }
+    }
+    shown.join("\n")
+}
+
+/// Keep only the lines selected by `limit`, without markers, for raw output
+/// such as `--stdout`. Line endings are kept as they are.
+pub fn limit_lines(code: &str, limit: LineLimit) -> String {
+    let lines: Vec<&str> = code.split_inclusive('\n').collect();
+    let (start, end) = limit.bounds(lines.len());
+    lines[start..end].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_bounds() {
+        let page = Page::new(Some(10), Some(3), 25);
+        assert_eq!((page.offset(), page.num_pages()), (20, 3));
+
+        // Defaults, and a page size larger than the result set
+        assert_eq!(Page::new(None, None, 4).size, 4);
+        assert_eq!(Page::new(None, Some(0), 0).num_pages(), 1);
+
+        assert_eq!(
+            console::strip_ansi_codes(&Page::new(Some(10), Some(2), 25).footer("")),
+            "page: 2/3 - total snippets: 25\n"
+        );
+    }
+
+    #[test]
+    fn test_colors_enabled() {
+        assert!(colors_enabled(ColorChoice::Auto, None, true));
+        assert!(!colors_enabled(ColorChoice::Auto, None, false));
+        assert!(!colors_enabled(ColorChoice::Auto, Some("1"), true));
+        assert!(colors_enabled(ColorChoice::Auto, Some(""), true));
+        assert!(colors_enabled(ColorChoice::Always, Some("1"), false));
+        assert!(!colors_enabled(ColorChoice::Never, None, true));
+    }
+
+    #[test]
+    fn test_query_matcher_is_case_insensitive_and_literal() {
+        let matcher = query_matcher("rust").unwrap();
+        assert_eq!(matcher.find_iter("Rust and rust").count(), 2);
+
+        let matcher = query_matcher("a.b").unwrap();
+        assert!(!matcher.is_match("axb"));
+        assert!(query_matcher("").is_none());
+    }
+
+    #[test]
+    fn test_highlight_matches_keeps_text() {
+        let matcher = query_matcher("rust");
+
+        let out = highlight_matches("Rust and rust", matcher.as_ref(), |s| s.bold());
+
+        assert_eq!(console::strip_ansi_codes(&out), "Rust and rust");
+    }
+
+    #[test]
+    fn test_desc_width_parsing() {
+        assert_eq!("80".parse(), Ok(DescWidth::Fixed(80)));
+        assert_eq!("AUTO".parse(), Ok(DescWidth::Auto));
+        assert!("wide".parse::<DescWidth>().is_err());
+
+        let width: DescWidth = serde_json::from_str("40").unwrap();
+        assert_eq!(width, DescWidth::Fixed(40));
+        let width: DescWidth = serde_json::from_str("\"auto\"").unwrap();
+        assert_eq!(width, DescWidth::Auto);
+        assert_eq!(serde_json::to_string(&DescWidth::Auto).unwrap(), "\"auto\"");
+    }
+
+    #[test]
+    fn test_to_json_styles() {
+        let value = serde_json::json!({"id": 1, "title": "x"});
+
+        assert!(!to_json(&value, JsonStyle::Compact).unwrap().contains('\n'));
+        assert!(to_json(&value, JsonStyle::Pretty).unwrap().contains('\n'));
+    }
+
+    #[test]
+    fn test_relative_time() {
+        let now = DateTime::parse_from_rfc3339("2024-06-30T12:00:00Z")
+            .unwrap()
+            .to_utc();
+        let before = |secs: i64| now - chrono::Duration::seconds(secs);
+
+        assert_eq!(relative_time(before(20), now), "just now");
+        assert_eq!(relative_time(before(60), now), "1 minute ago");
+        assert_eq!(relative_time(before(5 * 3600), now), "5 hours ago");
+        assert_eq!(relative_time(before(3 * 86400), now), "3 days ago");
+        assert_eq!(relative_time(before(60 * 86400), now), "2024-05-01");
+        // Clock skew puts timestamps slightly in the future
+        assert_eq!(relative_time(before(-30), now), "just now");
+    }
+
+    #[test]
+    fn test_render_snippets_plain() {
+        let snippet = Snippet {
+            id: 7,
+            title: "Deploy\tscript".to_string(),
+            description: "line one\nline two".to_string(),
+            categories: vec![],
+            fragments: vec![],
+            updated_at: String::new(),
+            share_count: 0,
+            is_public: false,
+        };
+
+        assert_eq!(
+            render_snippets_plain(&[snippet]),
+            "7\tDeploy script\tline one line two\n"
+        );
+    }
+
+    #[test]
+    fn test_render_code_plain() {
+        let code = "fn main() {}\n";
+        assert_eq!(render_code(code, CodeView::default()), code);
+    }
+
+    #[test]
+    fn test_render_code_line_numbers_are_right_aligned() {
+        let code = (1..=10)
+            .map(|i| format!("line {i}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let rendered = render_code(
+            &code,
+            CodeView {
+                line_numbers: true,
+                ..Default::default()
+            },
+        );
+        let rendered = console::strip_ansi_codes(&rendered);
+        let lines: Vec<&str> = rendered.lines().collect();
+
+        assert_eq!(lines[0], " 1 line 1");
+        assert_eq!(lines[9], "10 line 10");
+    }
+
+    #[test]
+    fn test_render_code_head_and_tail() {
+        let code = "a\nb\nc\nd\n";
+
+        let head = render_code(
+            code,
+            CodeView {
+                limit: Some(LineLimit::Head(2)),
+                ..Default::default()
+            },
+        );
+        assert_eq!(console::strip_ansi_codes(&head), "a\nb\n… (2 more lines)");
+
+        let tail = render_code(
+            code,
+            CodeView {
+                line_numbers: true,
+                limit: Some(LineLimit::Tail(1)),
+            },
+        );
+        assert_eq!(console::strip_ansi_codes(&tail), "… (3 more lines)\n4 d");
+
+        let all = render_code(
+            code,
+            CodeView {
+                limit: Some(LineLimit::Head(10)),
+                ..Default::default()
+            },
+        );
+        assert_eq!(all, "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn test_limit_lines_keeps_line_endings() {
+        let code = "a\nb\r\nc";
+        assert_eq!(limit_lines(code, LineLimit::Head(2)), "a\nb\r\n");
+        assert_eq!(limit_lines(code, LineLimit::Tail(1)), "c");
+        assert_eq!(limit_lines(code, LineLimit::Head(10)), code);
+    }
+}
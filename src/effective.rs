@@ -0,0 +1,115 @@
+use colored::*;
+use std::fmt;
+
+/// Where the effective value of a setting came from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Source {
+    Flag,
+    Env(&'static str),
+    File,
+    Keyring,
+    Default,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Flag => write!(f, "flag"),
+            Source::Env(var) => write!(f, "env {var}"),
+            Source::File => write!(f, "file"),
+            Source::Keyring => write!(f, "keyring"),
+            Source::Default => write!(f, "default"),
+        }
+    }
+}
+
+/// One resolved setting as shown by `config effective`
+#[derive(Debug, PartialEq)]
+pub struct Setting {
+    pub name: String,
+    pub value: String,
+    pub source: Source,
+}
+
+impl Setting {
+    pub fn new(name: impl Into<String>, value: impl Into<String>, source: Source) -> Setting {
+        Setting {
+            name: name.into(),
+            value: value.into(),
+            source,
+        }
+    }
+}
+
+/// Resolve a setting the way commands do: flag, then config file, then default
+pub fn resolve(name: &str, flag: Option<String>, file: Option<String>, default: &str) -> Setting {
+    match (flag, file) {
+        (Some(value), _) => Setting::new(name, value, Source::Flag),
+        (None, Some(value)) => Setting::new(name, value, Source::File),
+        (None, None) => Setting::new(name, default, Source::Default),
+    }
+}
+
+/// Aligned `name  value  (source)` lines
+pub fn render(settings: &[Setting]) -> String {
+    let name_width = settings.iter().map(|s| s.name.len()).max().unwrap_or(0);
+    let value_width = settings
+        .iter()
+        .map(|s| s.value.chars().count())
+        .max()
+        .unwrap_or(0);
+    settings
+        .iter()
+        .map(|s| {
+            let source = format!("({})", s.source);
+            format!(
+                "{:name_width$}  {:value_width$}  {}\n",
+                s.name.bold(),
+                s.value,
+                if s.source == Source::Default {
+                    source.dimmed()
+                } else {
+                    source.bright_purple()
+                }
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_precedence() {
+        let flag = Some("120".to_string());
+        let file = Some("80".to_string());
+
+        assert_eq!(
+            resolve("desc_width", flag, file.clone(), "60").source,
+            Source::Flag
+        );
+        assert_eq!(
+            resolve("desc_width", None, file, "60"),
+            Setting::new("desc_width", "80", Source::File)
+        );
+        assert_eq!(
+            resolve("desc_width", None, None, "60"),
+            Setting::new("desc_width", "60", Source::Default)
+        );
+    }
+
+    #[test]
+    fn test_render_aligns_columns() {
+        let output = render(&[
+            Setting::new("api_url", "https://stash.example", Source::File),
+            Setting::new("pager", "less -R", Source::Env("PAGER")),
+        ]);
+
+        assert_eq!(
+            console::strip_ansi_codes(&output),
+            "api_url  https://stash.example  (file)\n\
+             pager    less -R                (env PAGER)\n"
+        );
+    }
+}
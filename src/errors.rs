@@ -23,12 +23,30 @@ pub enum ByteStashyError {
     #[error("Invalid input: {0}")]
     InvalidInput(String),
 
+    #[error("Request timed out after {secs} seconds — is the server reachable?")]
+    Timeout { secs: u64 },
+
+    #[error("Server unreachable: {0}")]
+    Unreachable(String),
+
+    #[error("Snippet {id} not found")]
+    NotFound { id: usize },
+
     #[error("API error: HTTP {status} - {message}")]
     Api { status: u16, message: String },
 
+    #[error("`{command}` exited with {status}")]
+    CommandFailed {
+        command: String,
+        status: std::process::ExitStatus,
+    },
+
     #[error("JSON parsing failed: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("YAML serialization failed: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+
     #[error("Keyring error: {0}")]
     Keyring(#[from] keyring::Error),
 
@@ -55,7 +73,6 @@ impl ByteStashyError {
         }
     }
 
-    #[allow(dead_code)]
     /// Create API error with HTTP status
     pub fn api(status: u16, message: impl Into<String>) -> Self {
         Self::Api {
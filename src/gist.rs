@@ -0,0 +1,155 @@
+use crate::models::Snippet;
+use anyhow::{Context, Result};
+use reqwest::blocking::Client;
+use serde::Deserialize;
+use serde_json::{Map, Value, json};
+
+/// GitHub endpoint for creating gists
+const GISTS_URL: &str = "https://api.github.com/gists";
+
+#[derive(Deserialize)]
+struct GistResponse {
+    html_url: String,
+}
+
+/// Gist file name for `file_name`, numbered like `run-2.yml` when flattening
+/// made it collide with a name already in `taken`
+fn unique_file_name(file_name: &str, taken: &Map<String, Value>) -> String {
+    let flat = file_name.replace('/', "-");
+    if !taken.contains_key(&flat) {
+        return flat;
+    }
+    let (stem, extension) = match flat.rsplit_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{extension}")),
+        _ => (flat.as_str(), String::new()),
+    };
+    (2..)
+        .map(|n| format!("{stem}-{n}{extension}"))
+        .find(|name| !taken.contains_key(name))
+        .expect("an unused file name")
+}
+
+/// Build the gist request body for a snippet. Gist file names cannot contain
+/// slashes, so nested fragment paths are flattened.
+fn gist_payload(snippet: &Snippet, public: bool) -> Value {
+    let description = if snippet.description.is_empty() {
+        snippet.title.clone()
+    } else {
+        format!("{} – {}", snippet.title, snippet.description)
+    };
+    let mut files = Map::new();
+    for fragment in &snippet.fragments {
+        let name = unique_file_name(&fragment.file_name, &files);
+        files.insert(name, json!({ "content": fragment.code }));
+    }
+
+    json!({
+        "description": description,
+        "public": public,
+        "files": files,
+    })
+}
+
+/// Create a GitHub gist from a snippet and return its URL
+pub fn create_gist(
+    client: &Client,
+    snippet: &Snippet,
+    token: &str,
+    public: bool,
+) -> Result<String> {
+    let resp = client
+        .post(GISTS_URL)
+        .bearer_auth(token)
+        .header("Accept", "application/vnd.github+json")
+        .header("X-GitHub-Api-Version", "2022-11-28")
+        .header(
+            "User-Agent",
+            concat!("bytestashy/", env!("CARGO_PKG_VERSION")),
+        )
+        .json(&gist_payload(snippet, public))
+        .send()
+        .context("Error sending POST request to the GitHub gists API")?;
+
+    if !resp.status().is_success() {
+        let status = resp.status();
+        let text = resp.text().unwrap_or_default();
+        anyhow::bail!("GitHub rejected the gist: HTTP {} – {}", status, text);
+    }
+    let gist: GistResponse = resp
+        .json()
+        .context("Invalid response from the GitHub gists API")?;
+    Ok(gist.html_url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Fragment;
+
+    #[test]
+    fn test_gist_payload_maps_fragments() {
+        let fragment = |file_name: &str, code: &str| Fragment {
+            id: 0,
+            file_name: file_name.to_string(),
+            code: code.to_string(),
+            language: String::new(),
+            position: 0,
+        };
+        let snippet = Snippet {
+            id: 1,
+            title: "Deploy".to_string(),
+            description: "helper scripts".to_string(),
+            categories: vec![],
+            fragments: vec![
+                fragment("deploy.sh", "echo hi"),
+                fragment("ci/run.yml", "on: push"),
+            ],
+            updated_at: String::new(),
+            share_count: 0,
+            is_public: false,
+        };
+
+        let payload = gist_payload(&snippet, false);
+
+        assert_eq!(payload["description"], "Deploy – helper scripts");
+        assert_eq!(payload["public"], false);
+        assert_eq!(payload["files"]["deploy.sh"]["content"], "echo hi");
+        assert_eq!(payload["files"]["ci-run.yml"]["content"], "on: push");
+    }
+
+    #[test]
+    fn test_gist_payload_numbers_colliding_file_names() {
+        let fragment = |file_name: &str, code: &str| Fragment {
+            id: 0,
+            file_name: file_name.to_string(),
+            code: code.to_string(),
+            language: String::new(),
+            position: 0,
+        };
+        let snippet = Snippet {
+            id: 1,
+            title: "CI".to_string(),
+            description: String::new(),
+            categories: vec![],
+            fragments: vec![
+                fragment("ci-run.yml", "flat"),
+                fragment("ci/run.yml", "nested"),
+                fragment("ci/run.yml", "again"),
+                fragment(".env", "one"),
+                fragment(".env", "two"),
+            ],
+            updated_at: String::new(),
+            share_count: 0,
+            is_public: false,
+        };
+
+        let files = &gist_payload(&snippet, false)["files"];
+
+        assert_eq!(files.as_object().unwrap().len(), 5);
+        assert_eq!(files["ci-run.yml"]["content"], "flat");
+        assert_eq!(files["ci-run-2.yml"]["content"], "nested");
+        assert_eq!(files["ci-run-3.yml"]["content"], "again");
+        assert_eq!(files[".env"]["content"], "one");
+        assert_eq!(files[".env-2"]["content"], "two");
+    }
+}
@@ -0,0 +1,65 @@
+use crate::language;
+use std::sync::LazyLock;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+use syntect::util::{LinesWithEndings, as_24_bit_terminal_escaped};
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+const THEME: &str = "base16-ocean.dark";
+const RESET: &str = "\x1b[0m";
+
+/// Syntax for a ByteStash language name such as `rust` or `bash`
+fn find_syntax(language: &str) -> Option<&'static SyntaxReference> {
+    let language = language.trim();
+    if language.is_empty() || language.eq_ignore_ascii_case("plaintext") {
+        return None;
+    }
+    SYNTAXES.find_syntax_by_token(language).or_else(|| {
+        language::extension_for_language(language)
+            .and_then(|ext| SYNTAXES.find_syntax_by_extension(ext))
+    })
+}
+
+/// Highlight `code` for a 24-bit color terminal, or `None` when the language
+/// is unknown. Every line ends with a color reset so gutters stay unstyled.
+pub fn highlight(code: &str, language: &str) -> Option<String> {
+    let syntax = find_syntax(language)?;
+    let mut highlighter = HighlightLines::new(syntax, &THEMES.themes[THEME]);
+    let mut out = String::with_capacity(code.len() * 2);
+    for line in LinesWithEndings::from(code) {
+        let ranges = highlighter.highlight_line(line, &SYNTAXES).ok()?;
+        let escaped = as_24_bit_terminal_escaped(&ranges, false);
+        out.push_str(escaped.trim_end_matches(['\n', '\r']));
+        out.push_str(RESET);
+        if line.ends_with('\n') {
+            out.push('\n');
+        }
+    }
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_highlight_known_language() {
+        let code = "fn main() {\n    println!(\"hi\");\n}\n";
+
+        let highlighted = highlight(code, "rust").unwrap();
+
+        assert!(highlighted.contains("\x1b[38;2;"));
+        assert_eq!(highlighted.lines().count(), 3);
+        assert!(highlighted.lines().all(|line| line.ends_with(RESET)));
+    }
+
+    #[test]
+    fn test_highlight_unknown_language() {
+        assert_eq!(highlight("text", "plaintext"), None);
+        assert_eq!(highlight("text", ""), None);
+        assert_eq!(highlight("text", "no-such-language"), None);
+    }
+}
@@ -0,0 +1,92 @@
+use directories::ProjectDirs;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use tracing::warn;
+
+/// Maximum number of entries kept per prompt
+const MAX_ENTRIES: usize = 50;
+
+/// Input history for one prompt, stored one entry per line, newest first
+pub struct PromptHistory {
+    path: PathBuf,
+    entries: VecDeque<String>,
+}
+
+impl PromptHistory {
+    /// Load the history for `field` from the config directory
+    pub fn load(field: &str) -> Option<PromptHistory> {
+        let dir = ProjectDirs::from("", "", "bytestashy")
+            .map(|dirs| dirs.config_dir().join("history"))?;
+        Some(Self::load_from(dir.join(field)))
+    }
+
+    fn load_from(path: PathBuf) -> PromptHistory {
+        let entries = fs::read_to_string(&path)
+            .map(|content| {
+                content
+                    .lines()
+                    .filter(|line| !line.is_empty())
+                    .take(MAX_ENTRIES)
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+        PromptHistory { path, entries }
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let content: Vec<&str> = self.entries.iter().map(String::as_str).collect();
+        fs::write(&self.path, content.join("\n") + "\n")
+    }
+}
+
+impl dialoguer::History<String> for PromptHistory {
+    fn read(&self, pos: usize) -> Option<String> {
+        self.entries.get(pos).cloned()
+    }
+
+    fn write(&mut self, val: &String) {
+        if val.trim().is_empty() {
+            return;
+        }
+        self.entries.retain(|entry| entry != val);
+        self.entries.push_front(val.clone());
+        self.entries.truncate(MAX_ENTRIES);
+        // History is a convenience, a failed write must not abort the prompt
+        if let Err(err) = self.save() {
+            warn!("Could not save prompt history {:?}: {}", self.path, err);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dialoguer::History;
+
+    #[test]
+    fn test_history_dedups_caps_and_persists() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("title");
+
+        let mut history = PromptHistory::load_from(path.clone());
+        for i in 0..MAX_ENTRIES + 5 {
+            history.write(&format!("entry {i}"));
+        }
+        history.write(&"entry 10".to_string());
+        history.write(&"   ".to_string());
+
+        let reloaded = PromptHistory::load_from(path);
+        assert_eq!(reloaded.entries.len(), MAX_ENTRIES);
+        assert_eq!(reloaded.read(0).as_deref(), Some("entry 10"));
+        assert_eq!(reloaded.read(1), Some(format!("entry {}", MAX_ENTRIES + 4)));
+        assert_eq!(
+            reloaded.entries.iter().filter(|e| *e == "entry 10").count(),
+            1
+        );
+    }
+}
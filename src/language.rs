@@ -0,0 +1,135 @@
+use std::path::Path;
+
+/// File extension to language mapping, also used to pick extensions for languages.
+/// The first extension listed for a language is its preferred one.
+const LANGUAGES: &[(&str, &str)] = &[
+    ("rs", "rust"),
+    ("py", "python"),
+    ("pyw", "python"),
+    ("js", "javascript"),
+    ("mjs", "javascript"),
+    ("cjs", "javascript"),
+    ("jsx", "jsx"),
+    ("ts", "typescript"),
+    ("tsx", "tsx"),
+    ("go", "go"),
+    ("java", "java"),
+    ("kt", "kotlin"),
+    ("kts", "kotlin"),
+    ("swift", "swift"),
+    ("c", "c"),
+    ("h", "c"),
+    ("cpp", "cpp"),
+    ("cc", "cpp"),
+    ("cxx", "cpp"),
+    ("hpp", "cpp"),
+    ("cs", "csharp"),
+    ("rb", "ruby"),
+    ("php", "php"),
+    ("pl", "perl"),
+    ("lua", "lua"),
+    ("r", "r"),
+    ("scala", "scala"),
+    ("dart", "dart"),
+    ("ex", "elixir"),
+    ("exs", "elixir"),
+    ("erl", "erlang"),
+    ("hs", "haskell"),
+    ("clj", "clojure"),
+    ("sh", "bash"),
+    ("bash", "bash"),
+    ("zsh", "bash"),
+    ("fish", "fish"),
+    ("ps1", "powershell"),
+    ("bat", "batch"),
+    ("sql", "sql"),
+    ("html", "html"),
+    ("htm", "html"),
+    ("css", "css"),
+    ("scss", "scss"),
+    ("sass", "sass"),
+    ("less", "less"),
+    ("vue", "vue"),
+    ("svelte", "svelte"),
+    ("json", "json"),
+    ("yaml", "yaml"),
+    ("yml", "yaml"),
+    ("toml", "toml"),
+    ("xml", "xml"),
+    ("ini", "ini"),
+    ("md", "markdown"),
+    ("markdown", "markdown"),
+    ("tex", "latex"),
+    ("dockerfile", "dockerfile"),
+    ("tf", "hcl"),
+    ("hcl", "hcl"),
+    ("nix", "nix"),
+    ("vim", "vim"),
+    ("txt", "plaintext"),
+];
+
+/// Detect the language of a file from its extension
+pub fn language_for_path(path: &str) -> Option<&'static str> {
+    let path = Path::new(path);
+    let key = match path.extension() {
+        Some(ext) => ext.to_string_lossy().to_lowercase(),
+        // Extensionless files like `Dockerfile` are matched by name
+        None => path.file_name()?.to_string_lossy().to_lowercase(),
+    };
+    LANGUAGES
+        .iter()
+        .find(|(ext, _)| *ext == key)
+        .map(|(_, language)| *language)
+}
+
+/// Language sent for a file, falling back to `plaintext` for unknown extensions
+pub fn detect_language(path: &str) -> &'static str {
+    language_for_path(path).unwrap_or("plaintext")
+}
+
+/// Preferred file extension for a language, matched case-insensitively
+pub fn extension_for_language(language: &str) -> Option<&'static str> {
+    let language = language.trim().to_lowercase();
+    LANGUAGES
+        .iter()
+        .find(|(_, lang)| *lang == language)
+        .map(|(ext, _)| *ext)
+}
+
+/// Append the language's preferred extension to a file name that has none
+pub fn with_language_extension(file_name: &str, language: &str) -> String {
+    if Path::new(file_name).extension().is_some() {
+        return file_name.to_string();
+    }
+    match extension_for_language(language) {
+        Some(ext) => format!("{file_name}.{ext}"),
+        None => file_name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_for_path() {
+        assert_eq!(language_for_path("src/main.rs"), Some("rust"));
+        assert_eq!(language_for_path("script.PY"), Some("python"));
+        assert_eq!(language_for_path("Dockerfile"), Some("dockerfile"));
+        assert_eq!(language_for_path("archive.xyz"), None);
+        assert_eq!(language_for_path("README"), None);
+        assert_eq!(detect_language("README"), "plaintext");
+        assert_eq!(detect_language("lib/util.rs"), "rust");
+    }
+
+    #[test]
+    fn test_with_language_extension() {
+        assert_eq!(with_language_extension("scratch", "Rust"), "scratch.rs");
+        assert_eq!(
+            with_language_extension("dir/notes", "markdown"),
+            "dir/notes.md"
+        );
+        assert_eq!(with_language_extension("main.py", "rust"), "main.py");
+        assert_eq!(with_language_extension("scratch", "brainfuck"), "scratch");
+    }
+}
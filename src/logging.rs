@@ -0,0 +1,49 @@
+use std::io::IsTerminal;
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+
+/// Log level for the `-v`/`-q` flags: warnings by default, each `-v` one step
+/// more detail, `-q` errors only. `None` when no flag was given.
+fn level_for(verbose: u8, quiet: bool) -> Option<LevelFilter> {
+    if quiet {
+        return Some(LevelFilter::ERROR);
+    }
+    match verbose {
+        0 => None,
+        1 => Some(LevelFilter::INFO),
+        2 => Some(LevelFilter::DEBUG),
+        _ => Some(LevelFilter::TRACE),
+    }
+}
+
+/// Log to stderr. Without `-v`/`-q`, `RUST_LOG` is honored, defaulting to warnings.
+/// `color` is the resolved `--color`/`NO_COLOR` choice.
+pub fn init(verbose: u8, quiet: bool, color: bool) {
+    let filter = match level_for(verbose, quiet) {
+        Some(level) => EnvFilter::default().add_directive(level.into()),
+        None => EnvFilter::builder()
+            .with_default_directive(LevelFilter::WARN.into())
+            .from_env_lossy(),
+    };
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(std::io::stderr().is_terminal() && color)
+        .with_target(false)
+        .without_time()
+        .init();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_for_flags() {
+        assert_eq!(level_for(0, false), None);
+        assert_eq!(level_for(1, false), Some(LevelFilter::INFO));
+        assert_eq!(level_for(2, false), Some(LevelFilter::DEBUG));
+        assert_eq!(level_for(5, false), Some(LevelFilter::TRACE));
+        assert_eq!(level_for(0, true), Some(LevelFilter::ERROR));
+    }
+}
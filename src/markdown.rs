@@ -0,0 +1,75 @@
+use crate::models::Snippet;
+
+/// Backtick fence longer than any backtick run inside `code`
+fn fence_for(code: &str) -> String {
+    let longest = code.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Render a snippet as one Markdown document: title, description,
+/// categories and a fenced code block per fragment
+pub fn snippet_to_markdown(snippet: &Snippet) -> String {
+    let mut doc = format!("# {}\n\n", snippet.title);
+    if !snippet.description.is_empty() {
+        doc.push_str(&format!("{}\n\n", snippet.description));
+    }
+    if !snippet.categories.is_empty() {
+        for category in &snippet.categories {
+            doc.push_str(&format!("- {category}\n"));
+        }
+        doc.push('\n');
+    }
+    for fragment in &snippet.fragments {
+        let fence = fence_for(&fragment.code);
+        let language = match fragment.language.as_str() {
+            "plaintext" => "",
+            language => language,
+        };
+        doc.push_str(&format!("## {}\n\n{fence}{language}\n", fragment.file_name));
+        doc.push_str(&fragment.code);
+        if !fragment.code.is_empty() && !fragment.code.ends_with('\n') {
+            doc.push('\n');
+        }
+        doc.push_str(&format!("{fence}\n\n"));
+    }
+    doc.truncate(doc.trim_end().len());
+    doc.push('\n');
+    doc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Fragment;
+
+    #[test]
+    fn test_snippet_to_markdown() {
+        let fragment = |file_name: &str, code: &str, language: &str| Fragment {
+            id: 0,
+            file_name: file_name.to_string(),
+            code: code.to_string(),
+            language: language.to_string(),
+            position: 0,
+        };
+        let snippet = Snippet {
+            id: 1,
+            title: "Deploy".to_string(),
+            description: "Helper scripts".to_string(),
+            categories: vec!["ops".to_string(), "cli".to_string()],
+            fragments: vec![
+                fragment("deploy.sh", "echo hi", "bash"),
+                fragment("README.md", "```sh\nrun\n```\n", "markdown"),
+            ],
+            updated_at: String::new(),
+            share_count: 0,
+            is_public: false,
+        };
+
+        assert_eq!(
+            snippet_to_markdown(&snippet),
+            "# Deploy\n\nHelper scripts\n\n- ops\n- cli\n\n\
+             ## deploy.sh\n\n```bash\necho hi\n```\n\n\
+             ## README.md\n\n````markdown\n```sh\nrun\n```\n````\n"
+        );
+    }
+}
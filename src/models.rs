@@ -1,17 +1,20 @@
-use serde::Deserialize;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Deserializer, Serialize};
 
 /// A code fragment within a snippet
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Fragment {
     pub id: u64,
     pub file_name: String,
+    /// Left out by some servers when listing snippets
+    #[serde(default)]
     pub code: String,
     pub language: String,
     pub position: u64,
 }
 
 /// A complete code snippet with metadata and fragments
-#[derive(Deserialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct Snippet {
     pub id: u64,
     pub title: String,
@@ -20,6 +23,123 @@ pub struct Snippet {
     pub fragments: Vec<Fragment>,
     pub updated_at: String,
     pub share_count: u64,
+    #[serde(default, deserialize_with = "bool_or_int")]
+    pub is_public: bool,
+}
+
+/// Accept `true`/`false` as well as the `1`/`0` some servers send for flags
+fn bool_or_int<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Flag {
+        Bool(bool),
+        Int(i64),
+    }
+    Ok(match Flag::deserialize(deserializer)? {
+        Flag::Bool(value) => value,
+        Flag::Int(value) => value != 0,
+    })
+}
+
+impl Snippet {
+    /// Parse `updated_at`, accepting RFC 3339 and SQL-style timestamps
+    pub fn updated_at_time(&self) -> Option<DateTime<Utc>> {
+        parse_timestamp(&self.updated_at)
+    }
+}
+
+/// Parse a server timestamp; timestamps without an offset are treated as UTC
+pub fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Some(time.with_timezone(&Utc));
+    }
+    ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%dT%H:%M:%S%.f"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .map(|naive| naive.and_utc())
+}
+
+/// Reference to a snippet returned by create, update and delete
+#[derive(Debug, PartialEq)]
+pub struct SnippetRef {
+    pub id: String,
+    /// Web UI link to the snippet
+    pub url: String,
+}
+
+/// Optional server features detected by `probe`; `None` means inconclusive
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct Capabilities {
+    pub search_code: Option<bool>,
+    pub pagination: Option<bool>,
+    pub share_links: Option<bool>,
+    pub trash: Option<bool>,
+    pub pinning: Option<bool>,
+    pub categories: Option<bool>,
+    /// When the probe ran (RFC 3339)
+    pub probed_at: String,
+}
+
+impl Capabilities {
+    /// Capabilities as (name, support) rows for display
+    pub fn rows(&self) -> [(&'static str, Option<bool>); 6] {
+        [
+            ("search-code", self.search_code),
+            ("pagination", self.pagination),
+            ("share links", self.share_links),
+            ("trash/restore", self.trash),
+            ("pinning", self.pinning),
+            ("categories endpoint", self.categories),
+        ]
+    }
+}
+
+/// A fragment to upload from memory rather than from a file on disk
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct NewFragment {
+    pub file_name: String,
+    pub code: String,
+    #[serde(default)]
+    pub language: Option<String>,
+}
+
+/// A snippet to create from memory, e.g. from a bulk JSON payload
+#[derive(Deserialize, Debug, PartialEq)]
+pub struct NewSnippet {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub is_public: bool,
+    pub fragments: Vec<NewFragment>,
+}
+
+impl From<Snippet> for NewSnippet {
+    /// Contents and metadata of an existing snippet, ready to upload as a new one
+    fn from(snippet: Snippet) -> NewSnippet {
+        let mut fragments = snippet.fragments;
+        fragments.sort_by_key(|fragment| fragment.position);
+        NewSnippet {
+            title: snippet.title,
+            description: snippet.description,
+            categories: snippet.categories,
+            is_public: snippet.is_public,
+            fragments: fragments.into_iter().map(NewFragment::from).collect(),
+        }
+    }
+}
+
+impl From<Fragment> for NewFragment {
+    /// An existing fragment, to upload again
+    fn from(fragment: Fragment) -> NewFragment {
+        NewFragment {
+            file_name: fragment.file_name,
+            code: fragment.code,
+            language: Some(fragment.language).filter(|language| !language.is_empty()),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -99,4 +219,100 @@ mod tests {
         assert_eq!(snippet.categories.len(), 0);
         assert!(snippet.description.is_empty());
     }
+
+    #[test]
+    fn test_new_snippet_defaults() {
+        let json = r#"{
+            "title": "Bulk",
+            "fragments": [{"file_name": "a.sh", "code": "echo hi"}]
+        }"#;
+
+        let snippet: NewSnippet = serde_json::from_str(json).unwrap();
+
+        assert_eq!(snippet.title, "Bulk");
+        assert!(snippet.description.is_empty());
+        assert!(snippet.categories.is_empty());
+        assert!(!snippet.is_public);
+        assert_eq!(snippet.fragments[0].language, None);
+    }
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        let rfc = parse_timestamp("2023-01-01T10:00:00Z").unwrap();
+        let sql = parse_timestamp("2023-01-01 10:00:00").unwrap();
+
+        assert_eq!(rfc, sql);
+        assert!(parse_timestamp("yesterday").is_none());
+    }
+
+    #[test]
+    fn test_snippet_is_public_accepts_bool_and_int() {
+        let base = r#""id": 1, "title": "t", "description": "", "categories": [],
+            "fragments": [], "updated_at": "", "share_count": 0"#;
+
+        let snippet: Snippet =
+            serde_json::from_str(&format!("{{{base}, \"is_public\": 1}}")).unwrap();
+        assert!(snippet.is_public);
+        let snippet: Snippet =
+            serde_json::from_str(&format!("{{{base}, \"is_public\": false}}")).unwrap();
+        assert!(!snippet.is_public);
+        let snippet: Snippet = serde_json::from_str(&format!("{{{base}}}")).unwrap();
+        assert!(!snippet.is_public);
+    }
+
+    #[test]
+    fn test_snippet_serialization_round_trip() {
+        let json = r#"{"id":7,"title":"T","description":"","categories":["a"],"fragments":[{"id":1,"file_name":"a.rs","code":"x","language":"rust","position":0}],"updated_at":"2024-01-01 10:00:00","share_count":0,"is_public":1}"#;
+        let snippet: Snippet = serde_json::from_str(json).unwrap();
+
+        let serialized = serde_json::to_value(&snippet).unwrap();
+
+        assert_eq!(serialized["is_public"], true);
+        assert_eq!(serialized["fragments"][0]["file_name"], "a.rs");
+        assert_eq!(
+            serde_json::from_value::<Snippet>(serialized).unwrap(),
+            snippet
+        );
+    }
+
+    #[test]
+    fn test_new_snippet_from_snippet_keeps_fragment_order() {
+        let fragment = |file_name: &str, language: &str, position| Fragment {
+            id: 9,
+            file_name: file_name.to_string(),
+            code: format!("// {file_name}"),
+            language: language.to_string(),
+            position,
+        };
+        let snippet = Snippet {
+            id: 3,
+            title: "Tools".to_string(),
+            description: "Helpers".to_string(),
+            categories: vec!["cli".to_string()],
+            fragments: vec![fragment("b.rs", "rust", 1), fragment("a.txt", "", 0)],
+            updated_at: String::new(),
+            share_count: 4,
+            is_public: true,
+        };
+
+        let copy = NewSnippet::from(snippet);
+
+        assert_eq!((copy.title.as_str(), copy.is_public), ("Tools", true));
+        assert_eq!(copy.categories, vec!["cli"]);
+        assert_eq!(
+            copy.fragments,
+            vec![
+                NewFragment {
+                    file_name: "a.txt".to_string(),
+                    code: "// a.txt".to_string(),
+                    language: None,
+                },
+                NewFragment {
+                    file_name: "b.rs".to_string(),
+                    code: "// b.rs".to_string(),
+                    language: Some("rust".to_string()),
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,84 @@
+use std::env;
+use std::io::{IsTerminal, Write};
+use std::process::{Command, Stdio};
+use tracing::warn;
+
+/// Print `text`, piping it through a pager when stdout is a terminal and the
+/// text is taller than the screen, like `git log` does
+pub fn page(text: &str, enabled: bool) {
+    let stdout = std::io::stdout();
+    let fits = console::Term::stdout()
+        .size_checked()
+        .is_none_or(|(rows, _)| text.lines().count() < rows as usize);
+    if !enabled || !stdout.is_terminal() || fits {
+        print!("{text}");
+        return;
+    }
+
+    let Some(command) = pager_command(env::var("BYTESTASHY_PAGER").ok(), env::var("PAGER").ok())
+    else {
+        print!("{text}");
+        return;
+    };
+    if let Err(err) = run_pager(&command, text) {
+        warn!("Could not run pager {:?}: {}", command, err);
+        print!("{text}");
+    }
+}
+
+/// Pick the pager from `BYTESTASHY_PAGER`, then `PAGER`, then `less -R`.
+/// An empty value or `cat` disables paging.
+fn pager_command(bytestashy_pager: Option<String>, pager: Option<String>) -> Option<Vec<String>> {
+    let command = bytestashy_pager
+        .or(pager)
+        .unwrap_or_else(|| "less -R".to_string());
+    let parts: Vec<String> = command.split_whitespace().map(String::from).collect();
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+fn run_pager(command: &[String], text: &str) -> std::io::Result<()> {
+    let mut child = Command::new(&command[0])
+        .args(&command[1..])
+        // Same defaults as git: keep colors and quit when the output fits
+        .env(
+            "LESS",
+            env::var("LESS").unwrap_or_else(|_| "FRX".to_string()),
+        )
+        .stdin(Stdio::piped())
+        .spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe, which is fine
+        match stdin.write_all(text.as_bytes()) {
+            Err(err) if err.kind() != std::io::ErrorKind::BrokenPipe => return Err(err),
+            _ => {}
+        }
+    }
+    child.wait()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_command_precedence() {
+        assert_eq!(
+            pager_command(None, None),
+            Some(vec!["less".to_string(), "-R".to_string()])
+        );
+        assert_eq!(
+            pager_command(Some("most".to_string()), Some("more".to_string())),
+            Some(vec!["most".to_string()])
+        );
+        assert_eq!(
+            pager_command(None, Some("more".to_string())),
+            Some(vec!["more".to_string()])
+        );
+        assert_eq!(pager_command(Some(String::new()), None), None);
+        assert_eq!(pager_command(Some("cat".to_string()), None), None);
+    }
+}
@@ -0,0 +1,89 @@
+use crate::checksum::sha256_hex;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Progress of a bulk import, persisted so an interrupted run can be resumed
+#[derive(Serialize, Deserialize, Default)]
+struct StateFile {
+    source: String,
+    completed: BTreeSet<usize>,
+}
+
+/// Tracks which items of an import source were already created
+pub struct ResumeState {
+    path: PathBuf,
+    state: StateFile,
+}
+
+impl ResumeState {
+    /// Load the state for `source` with the given content, or start fresh.
+    /// The state is keyed by a hash of both, so an edited payload starts over.
+    pub fn load(source: &str, content: &[u8]) -> anyhow::Result<ResumeState> {
+        let dir = ProjectDirs::from("", "", "bytestashy")
+            .map(|dirs| dirs.cache_dir().join("import-state"))
+            .ok_or_else(|| anyhow::anyhow!("Could not determine cache directory"))?;
+        let key = sha256_hex(&[source.as_bytes(), b"\0", content].concat());
+        Self::load_from(dir.join(format!("{key}.json")), source)
+    }
+
+    /// Load the state stored at `path`, or start fresh
+    pub fn load_from(path: PathBuf, source: &str) -> anyhow::Result<ResumeState> {
+        let state = if path.exists() {
+            serde_json::from_str(&fs::read_to_string(&path)?)?
+        } else {
+            StateFile {
+                source: source.to_string(),
+                ..Default::default()
+            }
+        };
+        Ok(ResumeState { path, state })
+    }
+
+    /// Whether the item at `index` was created by a previous run
+    pub fn is_done(&self, index: usize) -> bool {
+        self.state.completed.contains(&index)
+    }
+
+    /// Record a created item, persisting immediately so a crash loses nothing
+    pub fn mark_done(&mut self, index: usize) -> anyhow::Result<()> {
+        self.state.completed.insert(index);
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, serde_json::to_string(&self.state)?)?;
+        Ok(())
+    }
+
+    /// Remove the state once every item has been imported
+    pub fn finish(self) -> anyhow::Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_state_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.json");
+
+        let mut state = ResumeState::load_from(path.clone(), "<stdin>").unwrap();
+        assert!(!state.is_done(1));
+        state.mark_done(1).unwrap();
+
+        let reloaded = ResumeState::load_from(path.clone(), "<stdin>").unwrap();
+        assert!(reloaded.is_done(1));
+        assert!(!reloaded.is_done(0));
+
+        reloaded.finish().unwrap();
+        assert!(!path.exists());
+    }
+}
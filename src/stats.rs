@@ -0,0 +1,212 @@
+use crate::models::Snippet;
+use colored::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+/// How often a category or language occurs
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Count {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Summary of a snippet library as shown by `stats`
+#[derive(Serialize, Debug, PartialEq)]
+pub struct Stats {
+    pub snippets: usize,
+    pub fragments: usize,
+    pub shares: u64,
+    /// Snippets per category, most used first
+    pub categories: Vec<Count>,
+    /// Fragments per language, most used first
+    pub languages: Vec<Count>,
+}
+
+/// Counts sorted by frequency, then name
+fn sorted_counts(counts: impl IntoIterator<Item = (String, usize)>) -> Vec<Count> {
+    let mut counts: Vec<Count> = counts
+        .into_iter()
+        .map(|(name, count)| Count { name, count })
+        .collect();
+    counts.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    counts
+}
+
+/// Snippets per category, most used first. Unless `case_sensitive`, categories
+/// differing only in case are counted together under their first spelling.
+pub fn category_counts(snippets: &[Snippet], case_sensitive: bool) -> Vec<Count> {
+    let mut counts: HashMap<String, (String, usize)> = HashMap::new();
+    for snippet in snippets {
+        let mut seen = HashSet::new();
+        for category in &snippet.categories {
+            let key = match case_sensitive {
+                true => category.clone(),
+                false => category.to_lowercase(),
+            };
+            if seen.insert(key.clone()) {
+                counts.entry(key).or_insert_with(|| (category.clone(), 0)).1 += 1;
+            }
+        }
+    }
+    sorted_counts(counts.into_values())
+}
+
+/// One aligned `name  count` line per entry
+pub fn render_counts(counts: &[Count]) -> String {
+    let width = counts
+        .iter()
+        .map(|c| c.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    counts
+        .iter()
+        .map(|count| {
+            format!(
+                "  {:width$}  {}\n",
+                count.name.bright_purple(),
+                count.count.to_string().bright_yellow().bold()
+            )
+        })
+        .collect()
+}
+
+impl Stats {
+    pub fn from_snippets(snippets: &[Snippet]) -> Stats {
+        let mut languages = HashMap::new();
+        for snippet in snippets {
+            for fragment in &snippet.fragments {
+                let language = match fragment.language.trim() {
+                    "" => "unknown",
+                    language => language,
+                };
+                *languages.entry(language.to_string()).or_default() += 1;
+            }
+        }
+
+        Stats {
+            snippets: snippets.len(),
+            fragments: snippets.iter().map(|s| s.fragments.len()).sum(),
+            shares: snippets.iter().map(|s| s.share_count).sum(),
+            categories: category_counts(snippets, true),
+            languages: sorted_counts(languages),
+        }
+    }
+
+    /// Totals followed by the category and language breakdowns
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for (label, value) in [
+            ("Snippets", self.snippets as u64),
+            ("Fragments", self.fragments as u64),
+            ("Shares", self.shares),
+        ] {
+            out.push_str(&format!(
+                "{:10} {}\n",
+                format!("{label}:").bold(),
+                value.to_string().bright_yellow().bold()
+            ));
+        }
+        for (heading, counts) in [
+            ("Categories", &self.categories),
+            ("Languages", &self.languages),
+        ] {
+            if counts.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("\n{}\n", heading.underline().bold()));
+            out.push_str(&render_counts(counts));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Fragment;
+
+    #[test]
+    fn test_stats_from_snippets() {
+        let fragment = |language: &str| Fragment {
+            id: 0,
+            file_name: String::new(),
+            code: String::new(),
+            language: language.to_string(),
+            position: 0,
+        };
+        let snippet = |categories: &[&str], fragments: Vec<Fragment>, share_count| Snippet {
+            id: 0,
+            title: String::new(),
+            description: String::new(),
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            fragments,
+            updated_at: String::new(),
+            share_count,
+            is_public: false,
+        };
+        let snippets = vec![
+            snippet(&["cli", "rust"], vec![fragment("rust"), fragment("")], 2),
+            snippet(&["rust"], vec![fragment("rust"), fragment("bash")], 1),
+            snippet(&[], vec![], 0),
+        ];
+
+        let stats = Stats::from_snippets(&snippets);
+
+        assert_eq!((stats.snippets, stats.fragments, stats.shares), (3, 4, 3));
+        let names = |counts: &[Count]| {
+            counts
+                .iter()
+                .map(|c| format!("{}={}", c.name, c.count))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names(&stats.categories), vec!["rust=2", "cli=1"]);
+        assert_eq!(
+            names(&stats.languages),
+            vec!["rust=2", "bash=1", "unknown=1"]
+        );
+
+        assert_eq!(
+            console::strip_ansi_codes(&render_counts(&stats.categories)),
+            "  rust  2\n  cli   1\n"
+        );
+        assert!(
+            console::strip_ansi_codes(&stats.render())
+                .starts_with("Snippets:  3\nFragments: 4\nShares:    3\n")
+        );
+    }
+
+    #[test]
+    fn test_category_counts_merge_case() {
+        let snippet = |categories: &[&str]| Snippet {
+            id: 0,
+            title: String::new(),
+            description: String::new(),
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            fragments: vec![],
+            updated_at: String::new(),
+            share_count: 0,
+            is_public: false,
+        };
+        let snippets = vec![
+            snippet(&["Rust", "rust"]),
+            snippet(&["rust", "cli"]),
+            snippet(&["Cli"]),
+            snippet(&["web"]),
+        ];
+        let names = |counts: Vec<Count>| {
+            counts
+                .into_iter()
+                .map(|c| format!("{}={}", c.name, c.count))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(category_counts(&snippets, false)),
+            vec!["Rust=2", "cli=2", "web=1"]
+        );
+        assert_eq!(
+            names(category_counts(&snippets, true)),
+            vec!["rust=2", "Cli=1", "Rust=1", "cli=1", "web=1"]
+        );
+    }
+}
@@ -0,0 +1,268 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the state file kept in a synced directory
+pub const STATE_FILE: &str = ".bytestashy-sync.json";
+
+/// Which side may be changed by a sync
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncDirection {
+    Pull,
+    Push,
+    Both,
+}
+
+impl SyncDirection {
+    fn pulls(self) -> bool {
+        self != SyncDirection::Push
+    }
+
+    fn pushes(self) -> bool {
+        self != SyncDirection::Pull
+    }
+}
+
+/// What to do with one file name
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SyncAction {
+    InSync,
+    /// Write the fragment to the local file
+    Pull,
+    /// Upload the local file
+    Push,
+    /// The fragment was removed on the server; remove the local file
+    DeleteLocal,
+    /// The local file was removed; drop the fragment
+    DeleteRemote,
+    /// Both sides changed since the last sync
+    Conflict,
+    /// The server changed, but the direction does not allow pulling
+    NotPulled,
+    /// The local file changed, but the direction does not allow pushing
+    NotPushed,
+}
+
+/// Content hashes per file name recorded at the end of the last sync
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq)]
+pub struct SyncState {
+    pub snippet_id: usize,
+    pub hashes: BTreeMap<String, String>,
+}
+
+impl SyncState {
+    /// Load the state of `dir`, starting fresh when it is missing or was
+    /// written for another snippet
+    pub fn load(dir: &Path, snippet_id: usize) -> anyhow::Result<SyncState> {
+        let path = dir.join(STATE_FILE);
+        if path.exists() {
+            let state: SyncState = serde_json::from_str(&fs::read_to_string(&path)?)?;
+            if state.snippet_id == snippet_id {
+                return Ok(state);
+            }
+        }
+        Ok(SyncState {
+            snippet_id,
+            ..Default::default()
+        })
+    }
+
+    pub fn save(&self, dir: &Path) -> anyhow::Result<()> {
+        fs::write(dir.join(STATE_FILE), serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Record the outcome of a sync; unresolved names keep their previous hash
+    pub fn apply(
+        &mut self,
+        plan: &[(String, SyncAction)],
+        local: &BTreeMap<String, String>,
+        remote: &BTreeMap<String, String>,
+    ) {
+        for (name, action) in plan {
+            let hash = match action {
+                SyncAction::InSync | SyncAction::Pull => remote.get(name),
+                SyncAction::Push => local.get(name),
+                SyncAction::DeleteLocal | SyncAction::DeleteRemote => None,
+                SyncAction::Conflict | SyncAction::NotPulled | SyncAction::NotPushed => continue,
+            };
+            match hash {
+                Some(hash) => self.hashes.insert(name.clone(), hash.clone()),
+                None => self.hashes.remove(name),
+            };
+        }
+    }
+}
+
+/// Decide per file name how to reconcile local and remote hashes against the
+/// hashes of the last sync
+pub fn plan_sync(
+    local: &BTreeMap<String, String>,
+    remote: &BTreeMap<String, String>,
+    base: &BTreeMap<String, String>,
+    direction: SyncDirection,
+) -> Vec<(String, SyncAction)> {
+    let names: BTreeSet<&String> = local
+        .keys()
+        .chain(remote.keys())
+        .chain(base.keys())
+        .collect();
+
+    names
+        .into_iter()
+        .map(|name| {
+            let (l, r, b) = (local.get(name), remote.get(name), base.get(name));
+            let action = if l == r {
+                SyncAction::InSync
+            } else {
+                match (l != b, r != b) {
+                    (true, true) => SyncAction::Conflict,
+                    (false, _) if !direction.pulls() => SyncAction::NotPulled,
+                    (false, _) if r.is_none() => SyncAction::DeleteLocal,
+                    (false, _) => SyncAction::Pull,
+                    (true, false) if !direction.pushes() => SyncAction::NotPushed,
+                    (true, false) if l.is_none() => SyncAction::DeleteRemote,
+                    (true, false) => SyncAction::Push,
+                }
+            };
+            (name.clone(), action)
+        })
+        .collect()
+}
+
+/// Files below `dir` keyed by their `/`-separated relative name.
+/// Hidden files and directories, including the state file, are skipped.
+pub fn local_files(dir: &Path) -> std::io::Result<BTreeMap<String, PathBuf>> {
+    let mut files = BTreeMap::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let entry = entry?;
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+            let path = entry.path();
+            if entry.file_type()?.is_dir() {
+                pending.push(path);
+            } else if let Ok(relative) = path.strip_prefix(dir) {
+                let name = relative
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.insert(name, path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Local path of a fragment, refusing names that would escape `dir`
+pub fn local_path(dir: &Path, file_name: &str) -> Option<PathBuf> {
+    let relative = Path::new(file_name);
+    let safe = relative
+        .components()
+        .all(|c| matches!(c, std::path::Component::Normal(_)));
+    (safe && !file_name.is_empty()).then(|| dir.join(relative))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hashes(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(name, hash)| (name.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_sync_both_directions() {
+        let base = hashes(&[
+            ("same", "1"),
+            ("pulled", "1"),
+            ("pushed", "1"),
+            ("both", "1"),
+            ("gone_remote", "1"),
+            ("gone_local", "1"),
+        ]);
+        let local = hashes(&[
+            ("same", "1"),
+            ("pulled", "1"),
+            ("pushed", "2"),
+            ("both", "2"),
+            ("gone_remote", "1"),
+            ("new_local", "1"),
+        ]);
+        let remote = hashes(&[
+            ("same", "1"),
+            ("pulled", "2"),
+            ("pushed", "1"),
+            ("both", "3"),
+            ("gone_local", "1"),
+            ("new_remote", "1"),
+        ]);
+
+        let plan: BTreeMap<String, SyncAction> =
+            plan_sync(&local, &remote, &base, SyncDirection::Both)
+                .into_iter()
+                .collect();
+
+        assert_eq!(plan["same"], SyncAction::InSync);
+        assert_eq!(plan["pulled"], SyncAction::Pull);
+        assert_eq!(plan["pushed"], SyncAction::Push);
+        assert_eq!(plan["both"], SyncAction::Conflict);
+        assert_eq!(plan["gone_remote"], SyncAction::DeleteLocal);
+        assert_eq!(plan["gone_local"], SyncAction::DeleteRemote);
+        assert_eq!(plan["new_local"], SyncAction::Push);
+        assert_eq!(plan["new_remote"], SyncAction::Pull);
+    }
+
+    #[test]
+    fn test_plan_sync_respects_direction_and_state() {
+        let base = BTreeMap::new();
+        let local = hashes(&[("a", "1")]);
+        let remote = hashes(&[("b", "1")]);
+
+        let plan = plan_sync(&local, &remote, &base, SyncDirection::Pull);
+        assert_eq!(
+            plan,
+            vec![
+                ("a".to_string(), SyncAction::NotPushed),
+                ("b".to_string(), SyncAction::Pull)
+            ]
+        );
+
+        let mut state = SyncState::default();
+        state.apply(&plan, &local, &remote);
+        assert_eq!(state.hashes, hashes(&[("b", "1")]));
+    }
+
+    #[test]
+    fn test_local_files_skips_hidden_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("src")).unwrap();
+        fs::create_dir_all(dir.path().join(".git")).unwrap();
+        fs::write(dir.path().join("src/main.rs"), "").unwrap();
+        fs::write(dir.path().join("README.md"), "").unwrap();
+        fs::write(dir.path().join(".git/HEAD"), "").unwrap();
+        fs::write(dir.path().join(STATE_FILE), "{}").unwrap();
+
+        let names: Vec<String> = local_files(dir.path()).unwrap().into_keys().collect();
+
+        assert_eq!(names, vec!["README.md", "src/main.rs"]);
+    }
+
+    #[test]
+    fn test_local_path_rejects_escapes() {
+        let dir = Path::new("/tmp/sync");
+        assert_eq!(
+            local_path(dir, "src/main.rs"),
+            Some(dir.join("src/main.rs"))
+        );
+        assert_eq!(local_path(dir, "../etc/passwd"), None);
+        assert_eq!(local_path(dir, "/etc/passwd"), None);
+    }
+}
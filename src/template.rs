@@ -0,0 +1,54 @@
+use regex::{Captures, Regex};
+use std::collections::BTreeMap;
+use std::sync::LazyLock;
+
+/// Matches `{{name}}` placeholders, allowing whitespace inside the braces
+static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{\s*([A-Za-z_][A-Za-z0-9_-]*)\s*\}\}").unwrap());
+
+/// Result of filling a template
+#[derive(Debug, PartialEq)]
+pub struct Rendered {
+    pub text: String,
+    /// Placeholders without a value, in order of first appearance; left untouched in `text`
+    pub unfilled: Vec<String>,
+}
+
+/// Substitute `{{name}}` placeholders with values from `vars`
+pub fn fill_placeholders(template: &str, vars: &BTreeMap<String, String>) -> Rendered {
+    let mut unfilled = Vec::new();
+    let text = PLACEHOLDER.replace_all(template, |caps: &Captures| match vars.get(&caps[1]) {
+        Some(value) => value.clone(),
+        None => {
+            if !unfilled.iter().any(|name| name == &caps[1]) {
+                unfilled.push(caps[1].to_string());
+            }
+            caps[0].to_string()
+        }
+    });
+    Rendered {
+        text: text.into_owned(),
+        unfilled,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_placeholders() {
+        let vars = BTreeMap::from([("name".to_string(), "Foo".to_string())]);
+
+        let rendered = fill_placeholders(
+            "struct {{name}};\nimpl {{ name }} {}\n// {{todo}} {{todo}}",
+            &vars,
+        );
+
+        assert_eq!(
+            rendered.text,
+            "struct Foo;\nimpl Foo {}\n// {{todo}} {{todo}}"
+        );
+        assert_eq!(rendered.unfilled, vec!["todo"]);
+    }
+}
@@ -0,0 +1,195 @@
+use crate::language;
+use crate::sync;
+use anyhow::{Context, Result};
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::path::{Component, Path, PathBuf};
+
+/// A local file and the fragment name it is uploaded as
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UploadFile {
+    pub path: String,
+    pub name: String,
+    /// Forced language; detected from the name when unset
+    pub language: Option<String>,
+}
+
+impl UploadFile {
+    /// Upload `path` under its base name
+    pub fn new(path: impl Into<String>) -> UploadFile {
+        let path = path.into();
+        let name = Path::new(&path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+        UploadFile::named(path, name)
+    }
+
+    pub fn named(path: impl Into<String>, name: impl Into<String>) -> UploadFile {
+        UploadFile {
+            path: path.into(),
+            name: name.into(),
+            language: None,
+        }
+    }
+
+    /// Language sent with the file
+    pub fn language(&self) -> &str {
+        self.language
+            .as_deref()
+            .unwrap_or_else(|| language::detect_language(&self.name))
+    }
+}
+
+fn is_glob(arg: &str) -> bool {
+    arg.contains(['*', '?', '['])
+}
+
+/// Leading components of a glob pattern that contain no wildcards
+fn glob_base(pattern: &str) -> PathBuf {
+    Path::new(pattern)
+        .components()
+        .take_while(|c| !is_glob(&c.as_os_str().to_string_lossy()))
+        .collect()
+}
+
+/// `/`-separated path of `path` below `base`
+fn relative_name(path: &Path, base: &Path) -> Option<String> {
+    let relative = path.strip_prefix(base).ok()?;
+    let parts: Vec<_> = relative
+        .components()
+        .map(|c| match c {
+            Component::Normal(part) => Some(part.to_string_lossy()),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    (!parts.is_empty()).then(|| parts.join("/"))
+}
+
+fn exclude_set(patterns: &[String]) -> Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        let pattern = pattern.trim_end_matches('/');
+        builder.add(Glob::new(pattern).with_context(|| format!("Invalid --exclude {pattern}"))?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Whether the name or any of its parent directories matches an exclude pattern
+fn is_excluded(excludes: &GlobSet, name: &str) -> bool {
+    let mut prefix = String::new();
+    name.split('/').any(|part| {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(part);
+        excludes.is_match(&prefix) || excludes.is_match(part)
+    })
+}
+
+/// Expand `create` arguments into the files to upload. Directories are walked
+/// recursively and glob patterns are matched; files found that way keep their
+/// path below the directory (or the pattern's fixed prefix) as fragment name,
+/// so `get` recreates the structure. Hidden entries are skipped.
+pub fn expand_inputs(args: &[String], exclude: &[String]) -> Result<Vec<UploadFile>> {
+    let excludes = exclude_set(exclude)?;
+    let mut files: Vec<UploadFile> = Vec::new();
+    let mut add = |file: UploadFile| {
+        if !is_excluded(&excludes, &file.name) && !files.iter().any(|f| f.path == file.path) {
+            files.push(file);
+        }
+    };
+
+    for arg in args {
+        let path = Path::new(arg);
+        if path.is_dir() {
+            let found = sync::local_files(path)
+                .with_context(|| format!("Couldn't read directory: {arg}"))?;
+            for (name, file) in found {
+                add(UploadFile::named(file.to_string_lossy(), name));
+            }
+        } else if !path.exists() && is_glob(arg) {
+            let options = glob::MatchOptions {
+                require_literal_leading_dot: true,
+                ..Default::default()
+            };
+            let base = glob_base(arg);
+            let mut matches: Vec<PathBuf> = glob::glob_with(arg, options)
+                .with_context(|| format!("Invalid glob pattern: {arg}"))?
+                .filter_map(|entry| entry.ok())
+                .filter(|p| p.is_file())
+                .collect();
+            matches.sort();
+            for file in matches {
+                let path = file.to_string_lossy().to_string();
+                match relative_name(&file, &base) {
+                    Some(name) => add(UploadFile::named(path, name)),
+                    None => add(UploadFile::new(path)),
+                }
+            }
+        } else {
+            add(UploadFile::new(arg.as_str()));
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn tree() -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        for file in [
+            "src/main.rs",
+            "src/cli/args.rs",
+            "target/debug/out.rs",
+            "README.md",
+        ] {
+            let path = dir.path().join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+        dir
+    }
+
+    fn names(files: &[UploadFile]) -> Vec<&str> {
+        files.iter().map(|f| f.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_expand_directory_keeps_relative_names() {
+        let dir = tree();
+        let root = dir.path().to_string_lossy().to_string();
+
+        let files = expand_inputs(&[root], &["target/".to_string()]).unwrap();
+
+        assert_eq!(
+            names(&files),
+            vec!["README.md", "src/cli/args.rs", "src/main.rs"]
+        );
+        assert!(files[1].path.ends_with("args.rs"));
+    }
+
+    #[test]
+    fn test_expand_glob_and_plain_files() {
+        let dir = tree();
+        let pattern = format!("{}/src/**/*.rs", dir.path().display());
+        let readme = format!("{}/README.md", dir.path().display());
+
+        let files = expand_inputs(&[pattern, readme.clone(), readme], &[]).unwrap();
+
+        assert_eq!(names(&files), vec!["cli/args.rs", "main.rs", "README.md"]);
+        assert_eq!(files[0].language(), "rust");
+        assert_eq!(files[2].language(), "markdown");
+    }
+
+    #[test]
+    fn test_expand_empty_matches() {
+        let dir = tree();
+        let pattern = format!("{}/*.py", dir.path().display());
+
+        assert!(expand_inputs(&[pattern], &[]).unwrap().is_empty());
+        assert!(expand_inputs(&["[".to_string()], &[]).is_err());
+    }
+}